@@ -0,0 +1,186 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::Error;
+use flate2::read::GzDecoder;
+
+/// Archive formats supported as diff roots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    TarGz,
+    Zip,
+}
+
+impl ArchiveKind {
+    /// Detect the archive kind from the file extension of `path`.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_index_json(name: &str) -> bool {
+    name == "index.json" || name.ends_with("/index.json")
+}
+
+/// Stream every `index.json` entry of the archive at `path` into `f`.
+///
+/// Entries are read one at a time, so the archive is never extracted as a
+/// whole. Entry names are relative to the archive root (a leading `./` is
+/// stripped) to match the keys produced when walking a directory.
+pub fn for_each_index_json(
+    path: &Path,
+    kind: ArchiveKind,
+    mut f: impl FnMut(String, String) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let file = BufReader::new(File::open(path)?);
+    match kind {
+        ArchiveKind::TarGz => {
+            let mut archive = tar::Archive::new(GzDecoder::new(file));
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                let name = name.strip_prefix("./").unwrap_or(&name).to_string();
+                if !is_index_json(&name) {
+                    continue;
+                }
+                let mut json_str = String::new();
+                entry.read_to_string(&mut json_str)?;
+                f(name, json_str)?;
+            }
+        }
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file)?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name();
+                let name = name.strip_prefix("./").unwrap_or(name).to_string();
+                if !is_index_json(&name) {
+                    continue;
+                }
+                let mut json_str = String::new();
+                entry.read_to_string(&mut json_str)?;
+                f(name, json_str)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io::Write;
+
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+    use crate::gather;
+
+    const FILES: &[(&str, &str)] = &[
+        ("en-us/docs/web/index.json", r#"{"doc":"web"}"#),
+        ("en-us/docs/web/css/index.json", r#"{"doc":"css"}"#),
+        ("en-us/docs/web/css/metadata.json", r#"{"skip":true}"#),
+        ("fr/docs/web/index.json", r#"{"doc":"fr"}"#),
+    ];
+
+    fn collect(path: &Path, kind: ArchiveKind) -> Result<BTreeMap<String, String>, Error> {
+        let mut out = BTreeMap::new();
+        for_each_index_json(path, kind, |name, json_str| {
+            out.insert(name, json_str);
+            Ok(())
+        })?;
+        Ok(out)
+    }
+
+    fn write_tar_gz(path: &Path) -> Result<(), Error> {
+        let gz = flate2::write::GzEncoder::new(File::create(path)?, Default::default());
+        let mut builder = tar::Builder::new(gz);
+        let mut dir = tar::Header::new_gnu();
+        dir.set_entry_type(tar::EntryType::Directory);
+        dir.set_size(0);
+        builder.append_data(&mut dir, "en-us/docs/", std::io::empty())?;
+        for (i, (name, content)) in FILES.iter().enumerate() {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            // Archives made with `tar -C build .` prefix every entry with `./`.
+            let name = if i % 2 == 0 {
+                format!("./{name}")
+            } else {
+                name.to_string()
+            };
+            builder.append_data(&mut header, name, content.as_bytes())?;
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    fn write_zip(path: &Path) -> Result<(), Error> {
+        let mut zip = zip::ZipWriter::new(File::create(path)?);
+        let options = SimpleFileOptions::default();
+        zip.add_directory("en-us/docs/", options)?;
+        for (i, (name, content)) in FILES.iter().enumerate() {
+            let name = if i % 2 == 0 {
+                format!("./{name}")
+            } else {
+                name.to_string()
+            };
+            zip.start_file(name, options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_for_each_index_json() -> Result<(), Error> {
+        let root = std::env::temp_dir().join(format!("diff-test-archive-{}", std::process::id()));
+        let tree = root.join("tree");
+        for (name, content) in FILES {
+            let path = tree.join(name);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content)?;
+        }
+        let result = (|| -> Result<(), Error> {
+            let walked = gather(&tree, &[], None, None, &[])?
+                .into_iter()
+                .map(|(key, value)| (key, value.to_string()))
+                .collect::<BTreeMap<_, _>>();
+            assert_eq!(
+                walked.keys().collect::<Vec<_>>(),
+                [
+                    "en-us/docs/web/css/index.json",
+                    "en-us/docs/web/index.json",
+                    "fr/docs/web/index.json"
+                ]
+            );
+
+            let tar_gz = root.join("build.tar.gz");
+            write_tar_gz(&tar_gz)?;
+            assert_eq!(ArchiveKind::detect(&tar_gz), Some(ArchiveKind::TarGz));
+            assert_eq!(collect(&tar_gz, ArchiveKind::TarGz)?, walked);
+
+            let zip = root.join("build.zip");
+            write_zip(&zip)?;
+            assert_eq!(ArchiveKind::detect(&zip), Some(ArchiveKind::Zip));
+            assert_eq!(collect(&zip, ArchiveKind::Zip)?, walked);
+            Ok(())
+        })();
+        fs::remove_dir_all(&root)?;
+        result
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+use std::env;
+use std::process::Command;
+
+use anyhow::{anyhow, Error};
+
+/// Keys of the `index.json` files built from sources changed since `git_ref`.
+///
+/// Runs `git diff` in `CONTENT_ROOT` if set, or else in the current directory.
+pub fn changed_since(git_ref: &str) -> Result<HashSet<String>, Error> {
+    let mut cmd = Command::new("git");
+    if let Some(root) = env::var_os("CONTENT_ROOT") {
+        cmd.arg("-C").arg(root);
+    }
+    let output = cmd
+        .args(["diff", "--name-only", "--relative", git_ref])
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git diff {git_ref} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .filter_map(output_key)
+        .collect())
+}
+
+/// Map a source file like `files/fr/web/html/index.md` to the key of its build output,
+/// `fr/docs/web/html/index.json`, relative to a build root holding the locale folders.
+fn output_key(source: &str) -> Option<String> {
+    let source = source.strip_prefix("files/").unwrap_or(source);
+    let (locale, rest) = source.split_once('/')?;
+    let folder = rest
+        .strip_suffix("/index.md")
+        .or_else(|| rest.strip_suffix("/index.html"))?;
+    Some(format!("{locale}/docs/{folder}/index.json"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_output_key() {
+        assert_eq!(
+            output_key("files/en-us/web/html/index.md").as_deref(),
+            Some("en-us/docs/web/html/index.json")
+        );
+        assert_eq!(
+            output_key("files/fr/web/x/index.md").as_deref(),
+            Some("fr/docs/web/x/index.json")
+        );
+        assert_eq!(
+            output_key("zh-cn/web/x/index.html").as_deref(),
+            Some("zh-cn/docs/web/x/index.json")
+        );
+        assert_eq!(output_key("files/fr/web/x/image.png"), None);
+    }
+}
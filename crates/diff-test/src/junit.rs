@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Error;
+use quick_xml::escape::escape;
+
+use crate::category;
+
+/// Write a JUnit XML report with one `<testcase>` per compared file.
+///
+/// Files without diffs pass, files with diffs fail with the diffs as the failure body.
+/// `results` holds plain text diffs, see [`crate::resolve_plain`].
+pub fn write_junit(
+    path: &Path,
+    results: &[(String, BTreeMap<String, String>)],
+    elapsed: Duration,
+) -> Result<(), Error> {
+    let tests = results.len();
+    let failures = results.iter().filter(|(_, diff)| !diff.is_empty()).count();
+    let file = File::create(path)?;
+    let mut out = BufWriter::new(file);
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<testsuites tests="{tests}" failures="{failures}" time="{:.3}">"#,
+        elapsed.as_secs_f64()
+    )?;
+    writeln!(
+        out,
+        r#"  <testsuite name="diff-test" tests="{tests}" failures="{failures}">"#
+    )?;
+    for (file, diff) in results {
+        let name = escape(file.as_str());
+        let classname = category(file);
+        let classname = escape(classname.as_str());
+        if diff.is_empty() {
+            writeln!(
+                out,
+                r#"    <testcase classname="{classname}" name="{name}"/>"#
+            )?;
+        } else {
+            writeln!(
+                out,
+                r#"    <testcase classname="{classname}" name="{name}">"#
+            )?;
+            let body = diff
+                .iter()
+                .map(|(path, diff)| format!("{path}:\n{diff}\n"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            writeln!(
+                out,
+                r#"      <failure message="{} paths differ">{}</failure>"#,
+                diff.len(),
+                escape(body.as_str())
+            )?;
+            writeln!(out, "    </testcase>")?;
+        }
+    }
+    writeln!(out, "  </testsuite>")?;
+    writeln!(out, "</testsuites>")?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_junit() -> Result<(), Error> {
+        let pass = "en-us/docs/web/html/a/index.json".to_string();
+        let fail = "en-us/docs/web/html/b/index.json".to_string();
+        let results = vec![
+            (pass.clone(), BTreeMap::new()),
+            (
+                fail.clone(),
+                BTreeMap::from([("doc.title".to_string(), "<a> & <b>".to_string())]),
+            ),
+        ];
+        let path = std::env::temp_dir().join("diff-test-junit.xml");
+        write_junit(&path, &results, Duration::from_millis(1500))?;
+        let xml = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert!(xml.contains(r#"<testsuites tests="2" failures="1" time="1.500">"#));
+        assert!(xml.contains(&format!(
+            r#"<testcase classname="{}" name="{pass}"/>"#,
+            category(&pass)
+        )));
+        assert!(xml.contains(&format!(
+            r#"<testcase classname="{}" name="{fail}">"#,
+            category(&fail)
+        )));
+        assert!(xml.contains(
+            r#"<failure message="1 paths differ">doc.title:
+&lt;a&gt; &amp; &lt;b&gt;
+</failure>"#
+        ));
+        Ok(())
+    }
+}
@@ -1,31 +1,51 @@
 use std::borrow::Cow;
 use std::cmp::max;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write;
 use std::fs;
 use std::fs::File;
-use std::io::{BufWriter, Write as _};
+use std::io::{BufWriter, IsTerminal, Write as _};
 use std::path::Path;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::Relaxed;
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::Duration;
 
 use anyhow::{anyhow, Error};
+use archive::{for_each_index_json, ArchiveKind};
 use base64::prelude::{Engine as _, BASE64_STANDARD_NO_PAD};
-use clap::{Args, Parser, Subcommand};
+use changed::changed_since;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use dashmap::DashMap;
 use ignore::types::TypesBuilder;
 use ignore::WalkBuilder;
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressFinish, ProgressStyle};
 use itertools::Itertools;
 use jsonpath_lib::Compiled;
-use lol_html::{element, rewrite_str, ElementContentHandlers, RewriteStrSettings, Selector};
+use junit::write_junit;
+use lol_html::html_content::ContentType;
+use lol_html::{
+    doc_text, element, rewrite_str, ElementContentHandlers, RewriteStrSettings, Selector,
+};
 use prettydiff::{diff_lines, diff_words};
+use quick_xml::escape::escape;
 use rayon::prelude::*;
 use regex::Regex;
+use remote::{fetch_index_jsons, remote_base};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
-use xml::fmt_html;
+use similar::{ChangeTag, TextDiff};
+use treemap::write_treemap;
+use watch::{snapshot, wait_for_change};
+use xml::{changed_elements, check_html, fmt_html};
 
+mod archive;
+mod changed;
+mod junit;
+mod remote;
+mod treemap;
+mod watch;
 mod xml;
 
 fn html(body: &str) -> String {
@@ -55,6 +75,10 @@ fn html(body: &str) -> String {
                     padding: .5rem;
                     background-color: lightgray;
                     grid-area: h;
+                    & > a {{
+                        margin-left: .5rem;
+                        font-size: smaller;
+                    }}
                 }}
                 & > div {{
                     padding: .5rem;
@@ -87,41 +111,118 @@ fn html(body: &str) -> String {
     )
 }
 
-pub(crate) fn walk_builder(path: &Path) -> Result<WalkBuilder, Error> {
+pub(crate) fn walk_builder(path: &Path, ignore_file: Option<&Path>) -> Result<WalkBuilder, Error> {
     let mut types = TypesBuilder::new();
     types.add_def("json:index.json")?;
     types.select("json");
     let mut builder = ignore::WalkBuilder::new(path);
     builder.types(types.build()?);
+    if let Some(ignore_file) = ignore_file {
+        if let Some(e) = builder.add_ignore(ignore_file) {
+            return Err(e.into());
+        }
+    }
     Ok(builder)
 }
 
-pub fn gather(path: &Path, selector: Option<&str>) -> Result<BTreeMap<String, Value>, Error> {
-    let template = if let Some(selector) = selector {
-        Some(Compiled::compile(selector).map_err(|e| anyhow!("{e}"))?)
-    } else {
-        None
+/// Whether `key` (like `fr/docs/web/index.json`) belongs to one of `locales`, compared
+/// case-insensitively. No `locales` means every locale.
+fn in_locales(key: &str, locales: &[String]) -> bool {
+    locales.is_empty()
+        || key.split('/').next().is_some_and(|locale| {
+            locales
+                .iter()
+                .any(|wanted| wanted.eq_ignore_ascii_case(locale))
+        })
+}
+
+pub fn gather(
+    path: &Path,
+    selectors: &[String],
+    ignore_file: Option<&Path>,
+    only: Option<&HashSet<String>>,
+    locales: &[String],
+) -> Result<BTreeMap<String, Value>, Error> {
+    let wanted = |key: &str| {
+        in_locales(key, locales)
+            && match only {
+                Some(only) => only.contains(key),
+                None => true,
+            }
+    };
+    let templates = selectors
+        .iter()
+        .map(|selector| {
+            Compiled::compile(selector)
+                .map(|template| (selector, template))
+                .map_err(|e| anyhow!("{e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let select = |template: &Compiled, index: &Value| {
+        template
+            .select(index)
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+    let extract = |json_str: &str| -> Result<Value, Error> {
+        let index: Value = serde_json::from_str(json_str)?;
+        Ok(match templates.as_slice() {
+            [] => index,
+            [(_, template)] => select(template, &index),
+            // Several selectors are compared together, keyed by selector.
+            templates => Value::Object(
+                templates
+                    .iter()
+                    .map(|(selector, template)| (selector.to_string(), select(template, &index)))
+                    .collect(),
+            ),
+        })
     };
-    walk_builder(path)?
+    if let Some(base) = remote_base(path) {
+        let keys = only.ok_or_else(|| anyhow!("a remote root needs the keys from a local root"))?;
+        return fetch_index_jsons(base, keys)?
+            .into_iter()
+            .map(|(key, json_str)| Ok((key, extract(&json_str)?)))
+            .collect();
+    }
+    if let Some(kind) = ArchiveKind::detect(path) {
+        let mut out = BTreeMap::new();
+        for_each_index_json(path, kind, |name, json_str| {
+            if !wanted(&name) {
+                return Ok(());
+            }
+            out.insert(name, extract(&json_str)?);
+            Ok(())
+        })?;
+        return Ok(out);
+    }
+    if !locales.is_empty() && path.join("docs").is_dir() {
+        return Err(anyhow!(
+            "{} is a single locale folder, --locale needs the build root holding the locale folders",
+            path.display()
+        ));
+    }
+    let mut builder = walk_builder(path, ignore_file)?;
+    if !locales.is_empty() {
+        // Prune the other locale folders instead of walking and filtering them.
+        let locales = locales.to_vec();
+        builder.filter_entry(move |entry| {
+            entry.depth() != 1 || in_locales(&entry.file_name().to_string_lossy(), &locales)
+        });
+    }
+    builder
         .build()
         .filter_map(Result::ok)
         .filter(|f| f.file_type().map(|ft| ft.is_file()).unwrap_or(false))
-        .map(|p| {
+        .map(|p| Ok::<_, Error>((p.path().strip_prefix(path)?.display().to_string(), p)))
+        .filter(|entry| entry.as_ref().map_or(true, |(key, _)| wanted(key)))
+        .map(|entry| {
+            let (key, p) = entry?;
             let json_str = fs::read_to_string(p.path())?;
-            let index: Value = serde_json::from_str(&json_str)?;
-
-            let extract = if let Some(template) = &template {
-                template
-                    .select(&index)
-                    .unwrap_or_default()
-                    .into_iter()
-                    .next()
-                    .cloned()
-                    .unwrap_or(Value::Null)
-            } else {
-                index
-            };
-            Ok::<_, Error>((p.path().strip_prefix(path)?.display().to_string(), extract))
+            Ok::<_, Error>((key, extract(&json_str)?))
         })
         .collect()
 }
@@ -142,10 +243,21 @@ enum Commands {
 }
 #[derive(Args)]
 struct BuildArgs {
+    /// Only compare the values selected by this jsonpath, can be repeated.
     #[arg(short, long)]
-    query: Option<String>,
+    query: Vec<String>,
+    /// Only compare files of this locale, like `fr` or `en-US`, can be repeated.
+    #[arg(long = "locale", value_name = "LOCALE")]
+    locales: Vec<String>,
     #[arg(short, long)]
     out: PathBuf,
+    /// Build directory or archive, either root can instead be an `http(s)://` base url.
+    ///
+    /// Roots hold the locale folders, so file keys look like `en-us/docs/web/index.json`.
+    /// A single locale folder like `build/en-us` works too, but not with `--locale`.
+    ///
+    /// For a url root the `index.json` keys found in the other root are fetched from
+    /// `<url>/<key>`, so only one root can be a url.
     root_a: PathBuf,
     root_b: PathBuf,
     #[arg(long)]
@@ -164,6 +276,92 @@ struct BuildArgs {
     verbose: bool,
     #[arg(long)]
     sidebars: bool,
+    #[arg(long)]
+    sidebars_only: bool,
+    #[arg(long, value_enum, default_value_t = SortBy::Path)]
+    sort_by: SortBy,
+    /// Re-run the diff whenever the roots change, polling every <WATCH_INTERVAL> seconds
+    #[arg(long)]
+    watch: bool,
+    /// Seconds between polls in --watch mode, each walking both roots
+    #[arg(long, default_value_t = 5)]
+    watch_interval: u64,
+    /// Skip files matching the gitignore-style patterns in <IGNORE_FILE> in both roots.
+    ///
+    /// Matching files are never gathered, so unlike the SKIP_GLOB_LIST entries (which are
+    /// applied when diffing) they don't count towards the totals at all. Patterns are
+    /// matched anywhere below the roots, e.g. `scratch/`. Not applied to archive roots.
+    #[arg(long)]
+    ignore_file: Option<PathBuf>,
+    /// Don't collapse whitespace around tags in html values before diffing
+    #[arg(long)]
+    no_ws_normalize: bool,
+    /// Don't drop empty `<p>` elements in html values before diffing
+    #[arg(long)]
+    keep_empty_p: bool,
+    /// Don't show a progress bar while diffing (never shown when stdout isn't a terminal)
+    #[arg(long)]
+    no_progress: bool,
+    /// Don't canonicalize void elements (`<br>` vs `<br/>`) in html values before diffing
+    #[arg(long)]
+    no_void_normalize: bool,
+    /// Truncate single diffs in the html report (with --value) to <MAX_DIFF_RENDER> chars.
+    /// The JUnit report keeps the full diffs.
+    #[arg(long)]
+    max_diff_render: Option<usize>,
+    /// Only compare the definition lists (`<dl>`) in html values
+    #[arg(long)]
+    dts_only: bool,
+    /// Only compare the visible text of html values, ignoring all markup
+    #[arg(long)]
+    text_only: bool,
+    /// Only compare pages whose source changed since <CHANGED_SINCE> (runs git in CONTENT_ROOT)
+    #[arg(long)]
+    changed_since: Option<String>,
+    /// Only compare the files listed in <INCLUDE>, one path per line or the output of --list-results
+    #[arg(long)]
+    include: Option<PathBuf>,
+    /// Instead of a report, only print the number of differing files and paths per category
+    #[arg(long)]
+    summary_only: bool,
+    /// Instead of a report, print `PASS <path>` or `FAIL <path> <n_diffs>` per file, sorted by path
+    #[arg(long)]
+    list_results: bool,
+    /// Compare arrays of scalars, or of objects with an identity key like `id` or `url`, regardless of order
+    #[arg(long)]
+    unordered_arrays: bool,
+    /// Instead of a report, list object keys present on only one side, aggregated over all files
+    #[arg(long)]
+    report_schema_additions: bool,
+    /// Print a ranking of the html elements most often involved in diffs
+    #[arg(long)]
+    tag_stats: bool,
+    /// Print a SHA-256 fingerprint of the set of non-allowlisted diffs
+    #[arg(long)]
+    fingerprint: bool,
+    /// Instead of diffing, report html values that aren't well-formed on either side
+    #[arg(long)]
+    validate_html: bool,
+    /// With --html, treat <OUT> as a directory and write one report per category plus an index.html
+    #[arg(long)]
+    split_by_category: bool,
+    /// Only report diffs missing from <SINCE_REPORT>, a report written by an earlier run with --csv
+    #[arg(long)]
+    since_report: Option<PathBuf>,
+    /// Write a JUnit XML report to <JUNIT>
+    #[arg(long)]
+    junit: Option<PathBuf>,
+    /// Write an html treemap of the number of diffs per directory to <TREEMAP>
+    #[arg(long)]
+    treemap: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortBy {
+    /// Order report entries by file path.
+    Path,
+    /// Order report entries by total diff size, largest first (only with --value).
+    Size,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -181,31 +379,301 @@ fn make_key(path: &[PathIndex]) -> String {
         .join(".")
 }
 
+/// Append the report section for the `(key, size, li)` entries of `category`.
+fn write_category(acc: &mut String, category: &str, entries: Vec<(String, usize, String)>) {
+    write!(
+        acc,
+        r#"<li><details><summary>[{}] {category}</summary><ul>{}</ul></details></li>"#,
+        entries.len(),
+        entries.into_iter().map(|(_, _, li)| li).collect::<String>(),
+    )
+    .unwrap();
+}
+
+/// Base url for linking to the files under `root`: the url itself for remote roots and a
+/// `file://` url for directories. Files inside archives can't be linked to.
+fn source_base(root: &Path) -> Option<String> {
+    if let Some(base) = remote_base(root) {
+        return Some(base.trim_end_matches('/').to_string());
+    }
+    if ArchiveKind::detect(root).is_some() {
+        return None;
+    }
+    let root = fs::canonicalize(root).ok()?;
+    Some(format!("file://{}", root.display()))
+}
+
+/// The header of a report entry: the key plus links to its `index.json` under each root.
+fn item_header(key: &str, sources: &[Option<String>; 2]) -> String {
+    let mut header = format!("<span>{key}");
+    for (side, base) in ["a", "b"].iter().zip(sources) {
+        if let Some(base) = base {
+            write!(
+                header,
+                r#"<a href="{base}/{key}" title="{base}/{key}">{side}</a>"#
+            )
+            .unwrap();
+        }
+    }
+    header.push_str("</span>");
+    header
+}
+
+/// Group a file key into its report category, e.g. `en-us/docs/web/css`, or
+/// `docs/web/css` for keys from a single locale folder.
+pub(crate) fn category(key: &str) -> String {
+    let p = key.splitn(5, '/').collect::<Vec<_>>();
+    match &p[..] {
+        ["docs", "web", cat, ..] => format!("docs/web/{cat}"),
+        ["docs", cat, ..] => format!("docs/{cat}"),
+        [locale, "docs", "web", cat, ..] => format!("{locale}/docs/web/{cat}"),
+        [locale, "docs", cat, ..] => format!("{locale}/docs/{cat}"),
+        [locale, cat, ..] => format!("{locale}/{cat}"),
+        [cat] => cat.to_string(),
+        [] => "".to_string(),
+    }
+}
+
 fn is_html(s: &str) -> bool {
     s.trim_start().starts_with('<') && s.trim_end().ends_with('>')
 }
 
+/// JSON paths whose diffs are dropped. An entry matches that exact path, an entry ending in
+/// `.*` matches the path before it and everything below it.
 const IGNORED_KEYS: &[&str] = &[
-    "doc.flaws",
+    "doc.flaws.*",
     "doc.modified",
     "doc.popularity",
     "doc.source.github_url",
     "doc.source.last_commit_url",
-    "doc.other_translations",
+    "doc.other_translations.*",
 ];
 
+fn is_ignored_key(key: &str, ignored: &[&str]) -> bool {
+    ignored.iter().any(|i| match i.strip_suffix(".*") {
+        Some(prefix) => key
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.')),
+        None => key == *i,
+    })
+}
+
+/// `(file prefix, key)` pairs: like [`IGNORED_KEYS`], but only for files under the prefix,
+/// e.g. `("en-us/docs/web/svg/", "doc.body.*")`.
+const SCOPED_IGNORED_KEYS: &[(&str, &str)] = &[];
+
+/// `(file prefix, key)` pairs whose diffs are suppressed and counted as allowlisted, keys
+/// match like in [`IGNORED_KEYS`].
+const SCOPED_ALLOWLIST: &[(&str, &str)] = &[];
+
+/// Whether a rule in `rules` covers `key` in `file`.
+fn is_scoped_key(file: &str, key: &str, rules: &[(&str, &str)]) -> bool {
+    rules
+        .iter()
+        .any(|(prefix, pattern)| file.starts_with(prefix) && is_ignored_key(key, &[pattern]))
+}
+
+/// File → JSON paths already reported by the `--since-report` run, these diffs are dropped.
+static KNOWN_DIFFS: OnceLock<HashMap<String, HashSet<String>>> = OnceLock::new();
+
+/// Parse the `File;JSON Path` lines of a `--csv` report.
+fn parse_csv_report(csv: &str) -> HashMap<String, HashSet<String>> {
+    let mut known = HashMap::<String, HashSet<String>>::new();
+    for (file, path) in csv.lines().skip(1).filter_map(|line| line.split_once(';')) {
+        known
+            .entry(file.to_string())
+            .or_default()
+            .insert(path.to_string());
+    }
+    known
+}
+
+const SIDEBAR_KEYS: &[&str] = &["doc.sidebarHTML", "doc.sidebarMacro"];
+
+/// File prefixes whose diffs are dropped. Files excluded by `--ignore-file` never get here.
 static SKIP_GLOB_LIST: LazyLock<Vec<&str>> = LazyLock::new(Vec::new);
 
 static ALLOWLIST: LazyLock<HashSet<(&str, &str)>> = LazyLock::new(|| vec![].into_iter().collect());
 
+/// Files whose diffs are all suppressed, e.g. pages known to differ wholesale during a migration.
+static FILE_ALLOWLIST: LazyLock<HashSet<&str>> = LazyLock::new(|| vec![].into_iter().collect());
+
+/// Number of differing leaf paths hidden per file by [`FILE_ALLOWLIST`].
+static FILE_ALLOWLIST_HIDDEN: LazyLock<Arc<DashMap<String, usize>>> =
+    LazyLock::new(|| Arc::new(DashMap::new()));
+
 static WS_DIFF: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"(?<x>>)[\n ]+|[\n ]+(?<y></)"#).unwrap());
 
 static EMPTY_P_DIFF: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"<p>[\n ]*</p>"#).unwrap());
 
+/// Hash of both sides → diff with ANSI colors, so identical diffs are only computed once.
 static DIFF_MAP: LazyLock<Arc<DashMap<String, String>>> =
     LazyLock::new(|| Arc::new(DashMap::new()));
 
+/// Stands in for a rendered diff, followed by its [`DIFF_MAP`] hash, until [`resolve_refs`].
+const REF_PLACEHOLDER: &str = "\0ref:";
+
+/// Render the diffs in `results` as html, swapping placeholders for their diffs.
+///
+/// With `max_render` each diff is truncated to that many chars before rendering.
+/// A diff showing up more than once is only rendered at its first occurrence, labeled
+/// `ref-N: `, later occurrences read `See ref-N`. Files and paths are walked in sorted order
+/// and references numbered along the way, so the same inputs always get the same references.
+fn resolve_refs(results: &mut [(String, BTreeMap<String, String>)], max_render: Option<usize>) {
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut counts = HashMap::<String, usize>::new();
+    for (_, diff) in results.iter() {
+        for hash in diff
+            .values()
+            .filter_map(|v| v.strip_prefix(REF_PLACEHOLDER))
+        {
+            *counts.entry(hash.to_string()).or_default() += 1;
+        }
+    }
+    let render = |diff: &str| {
+        let mut diff = diff.to_string();
+        if let Some(max) = max_render {
+            truncate_diff(&mut diff, max);
+        }
+        ansi_to_html::convert(&diff).unwrap()
+    };
+    let mut refs = HashMap::<String, usize>::new();
+    for (_, diff) in results.iter_mut() {
+        for value in diff.values_mut() {
+            let Some(hash) = value.strip_prefix(REF_PLACEHOLDER).map(str::to_string) else {
+                *value = render(value);
+                continue;
+            };
+            let plain = DIFF_MAP
+                .get(&hash)
+                .map(|plain| plain.clone())
+                .unwrap_or_default();
+            if counts[&hash] == 1 {
+                *value = render(&plain);
+                continue;
+            }
+            let next = refs.len() + 1;
+            *value = match refs.entry(hash) {
+                Entry::Occupied(entry) => format!("See ref-{}", entry.get()),
+                Entry::Vacant(entry) => {
+                    format!("ref-{}: {}", entry.insert(next), render(&plain))
+                }
+            };
+        }
+    }
+}
+
+/// Values recorded per `(file, json path)`.
+type PathMap<T> = Arc<DashMap<(String, String), T>>;
+
+/// `(file, json path)` → hash of both sides, recorded for `--fingerprint`.
+static DIFF_HASHES: LazyLock<PathMap<String>> = LazyLock::new(|| Arc::new(DashMap::new()));
+
+/// `(file, json path)` → number of changed chars, recorded for `--sort-by size`.
+static DIFF_SIZES: LazyLock<PathMap<usize>> = LazyLock::new(|| Arc::new(DashMap::new()));
+
+/// `(file, json path)` → elements touched by the html diff, recorded for `--tag-stats`.
+static DIFF_ELEMENTS: LazyLock<PathMap<Vec<String>>> = LazyLock::new(|| Arc::new(DashMap::new()));
+
+/// Tally the recorded elements, most frequent first.
+fn tag_stats() -> String {
+    let mut counts = BTreeMap::<&str, usize>::new();
+    let entries = DIFF_ELEMENTS.iter().collect::<Vec<_>>();
+    for entry in &entries {
+        for element in entry.value() {
+            *counts.entry(element.as_str()).or_default() += 1;
+        }
+    }
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    // Stable sort, so equally frequent elements stay in name order.
+    counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+    counts
+        .into_iter()
+        .map(|(element, count)| format!("{element}: {count}"))
+        .join(", ")
+}
+
+/// Number of chars in the words removed from `lhs` or added in `rhs`.
+fn changed_chars(lhs: &str, rhs: &str) -> usize {
+    TextDiff::from_words(lhs, rhs)
+        .iter_all_changes()
+        .filter(|change| change.tag() != ChangeTag::Equal)
+        .map(|change| change.value().chars().count())
+        .sum()
+}
+
+fn diff_hash(lhs: &str, rhs: &str) -> String {
+    let mut diff_hash = Sha256::new();
+    diff_hash.write_all(lhs.as_bytes()).unwrap();
+    diff_hash.write_all(rhs.as_bytes()).unwrap();
+    BASE64_STANDARD_NO_PAD.encode(&diff_hash.finalize()[..])
+}
+
+/// Swap the placeholders in `results` for their diffs as plain text, without colors.
+///
+/// Unlike [`resolve_refs`] every diff is written out, so each file stands on its own.
+fn resolve_plain(results: &mut [(String, BTreeMap<String, String>)]) {
+    for (_, diff) in results.iter_mut() {
+        for value in diff.values_mut() {
+            let colored = match value.strip_prefix(REF_PLACEHOLDER) {
+                Some(hash) => DIFF_MAP
+                    .get(hash)
+                    .map(|diff| diff.clone())
+                    .unwrap_or_default(),
+                None => value.clone(),
+            };
+            *value = visible_char_indices(&colored).map(|(_, c)| c).collect();
+        }
+    }
+}
+
+/// The chars of `text` with their byte offsets, skipping ANSI escape sequences like `\x1b[31m`.
+fn visible_char_indices(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut in_escape = false;
+    text.char_indices().filter(move |&(_, c)| {
+        if in_escape {
+            in_escape = !c.is_ascii_alphabetic();
+            false
+        } else {
+            in_escape = c == '\x1b';
+            !in_escape
+        }
+    })
+}
+
+/// Cut the colored `diff` down to `max` visible chars, noting how much was dropped.
+///
+/// Escape sequences are never cut, and colors are reset before the note.
+fn truncate_diff(diff: &mut String, max: usize) {
+    let Some((end, _)) = visible_char_indices(diff).nth(max) else {
+        return;
+    };
+    let more = visible_char_indices(&diff[end..]).count();
+    diff.truncate(end);
+    write!(diff, "\x1b[0m… (truncated, {more} more chars)").unwrap();
+}
+
+/// Hash the recorded diffs in sorted order, so the result doesn't depend on scheduling.
+fn fingerprint() -> String {
+    let mut hashes = DIFF_HASHES
+        .iter()
+        .map(|entry| {
+            let (file, path) = entry.key();
+            (file.clone(), path.clone(), entry.value().clone())
+        })
+        .collect::<Vec<_>>();
+    hashes.sort();
+    let mut fingerprint = Sha256::new();
+    for (file, path, hash) in hashes {
+        for part in [file, path, hash] {
+            fingerprint.write_all(part.as_bytes()).unwrap();
+            fingerprint.write_all(b"\0").unwrap();
+        }
+    }
+    format!("{:x}", fingerprint.finalize())
+}
+
 /// Run html content through these handlers to clean up the html before minifying and diffing.
 fn pre_diff_element_massaging_handlers<'a>(
     _args: &BuildArgs,
@@ -224,43 +692,433 @@ fn pre_diff_element_massaging_handlers<'a>(
     handlers
 }
 
-fn full_diff(
+/// Keep only the outermost `<dl>` elements of `html`.
+fn extract_dls(html: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut i = 0;
+    while let Some(offset) = html[i..].find('<') {
+        i += offset;
+        let rest = &html[i..];
+        if rest.starts_with("<dl")
+            && rest[3..].starts_with(|c: char| c == '>' || c.is_ascii_whitespace())
+        {
+            if depth == 0 {
+                start = i;
+            }
+            depth += 1;
+        } else if rest.starts_with("</dl>") && depth > 0 {
+            depth -= 1;
+            if depth == 0 {
+                out.push_str(&html[start..i + "</dl>".len()]);
+            }
+        }
+        i += 1;
+    }
+    out
+}
+
+const VOID_ELEMENTS: &str =
+    "area, base, br, col, embed, hr, img, input, link, meta, source, track, wbr";
+
+/// Rewrite every void element as `<name attr="value"/>`, so `<br>` and `<br />` or
+/// unquoted and quoted attribute values compare equal.
+fn normalize_void_elements(html: &str) -> Result<String, Error> {
+    Ok(rewrite_str(
+        html,
+        RewriteStrSettings {
+            element_content_handlers: vec![element!(VOID_ELEMENTS, |el| {
+                let mut tag = format!("<{}", el.tag_name());
+                for attr in el.attributes() {
+                    write!(tag, r#" {}="{}""#, attr.name(), escape(attr.value())).unwrap();
+                }
+                tag.push_str("/>");
+                el.replace(&tag, ContentType::Html);
+                Ok(())
+            })],
+            ..RewriteStrSettings::new()
+        },
+    )?)
+}
+
+/// Clean up, massage and minify html the same way for validating and diffing.
+fn massage_html(html: &str, args: &BuildArgs) -> Result<String, Error> {
+    let html = if args.no_ws_normalize {
+        Cow::Borrowed(html)
+    } else {
+        WS_DIFF.replace_all(html, "$x$y")
+    };
+    let html = if args.keep_empty_p {
+        html
+    } else {
+        Cow::Owned(EMPTY_P_DIFF.replace_all(&html, "").into_owned())
+    };
+    let html = rewrite_str(
+        &html,
+        RewriteStrSettings {
+            element_content_handlers: pre_diff_element_massaging_handlers(args),
+            ..RewriteStrSettings::new()
+        },
+    )?;
+    let html = if args.no_void_normalize {
+        html
+    } else {
+        normalize_void_elements(&html)?
+    };
+    html_minifier::minify(html).map_err(|e| anyhow!("{e}"))
+}
+
+/// Text content of `html` with all markup dropped and whitespace collapsed.
+fn text_content(html: &str) -> Result<String, Error> {
+    let mut text = String::new();
+    rewrite_str(
+        html,
+        RewriteStrSettings {
+            document_content_handlers: vec![doc_text!(|t| {
+                text.push_str(t.as_str());
+                Ok(())
+            })],
+            ..RewriteStrSettings::new()
+        },
+    )?;
+    Ok(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Collect the json path and error of every html string in `value` that isn't well-formed.
+fn validate_html(
+    value: &Value,
+    path: &[PathIndex],
+    errors: &mut Vec<(String, String)>,
+    args: &BuildArgs,
+) {
+    match value {
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                let mut path = path.to_vec();
+                path.push(PathIndex::Array(i));
+                validate_html(value, &path, errors, args);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                let mut path = path.to_vec();
+                path.push(PathIndex::Object(key.to_string()));
+                validate_html(value, &path, errors, args);
+            }
+        }
+        Value::String(s) if is_html(s) => {
+            if let Err(e) = massage_html(s, args)
+                .map_err(|e| e.to_string())
+                .and_then(|html| check_html(&html))
+            {
+                errors.push((make_key(path), e));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Number of leaf paths differing between `lhs` and `rhs`, without any normalization.
+fn count_leaf_diffs(lhs: &Value, rhs: &Value) -> usize {
+    match (lhs, rhs) {
+        _ if lhs == rhs => 0,
+        (Value::Array(lhs), Value::Array(rhs)) => (0..max(lhs.len(), rhs.len()))
+            .map(|i| {
+                count_leaf_diffs(
+                    lhs.get(i).unwrap_or(&Value::Null),
+                    rhs.get(i).unwrap_or(&Value::Null),
+                )
+            })
+            .sum(),
+        (Value::Object(lhs), Value::Object(rhs)) => lhs
+            .keys()
+            .chain(rhs.keys().filter(|k| !lhs.contains_key(*k)))
+            .map(|k| {
+                count_leaf_diffs(
+                    lhs.get(k).unwrap_or(&Value::Null),
+                    rhs.get(k).unwrap_or(&Value::Null),
+                )
+            })
+            .sum(),
+        _ => 1,
+    }
+}
+
+/// Whether differences at `path` are not reported, counting allowlisted differences.
+fn skip_path(
     lhs: &Value,
     rhs: &Value,
     file: &str,
     path: &[PathIndex],
-    diff: &mut BTreeMap<String, String>,
+    key: &str,
+    allowlisted: &mut usize,
     args: &BuildArgs,
-) {
+) -> bool {
     if path.len() == 1 {
         if let PathIndex::Object(s) = &path[0] {
             if s == "url" {
-                return;
+                return true;
             }
         }
     }
-    let key = make_key(path);
+
+    // With --sidebars-only, only descend into paths leading to a sidebar key.
+    if args.sidebars_only
+        && !SIDEBAR_KEYS
+            .iter()
+            .any(|k| k.starts_with(key) || key.starts_with(k))
+    {
+        return true;
+    }
 
     if SKIP_GLOB_LIST.iter().any(|i| file.starts_with(i)) {
+        return true;
+    }
+
+    if path.is_empty() && FILE_ALLOWLIST.contains(file) {
+        if lhs != rhs {
+            *allowlisted += 1;
+            FILE_ALLOWLIST_HIDDEN.insert(file.to_string(), count_leaf_diffs(lhs, rhs));
+        }
+        return true;
+    }
+
+    if KNOWN_DIFFS
+        .get()
+        .and_then(|known| known.get(file))
+        .is_some_and(|paths| paths.contains(key))
+    {
+        return true;
+    }
+
+    if ALLOWLIST.contains(&(file, key)) || is_scoped_key(file, key, SCOPED_ALLOWLIST) {
+        if lhs != rhs {
+            *allowlisted += 1;
+        }
+        return true;
+    }
+
+    lhs != rhs
+        && (is_ignored_key(key, IGNORED_KEYS)
+            || is_scoped_key(file, key, SCOPED_IGNORED_KEYS)
+            || key == "doc.sidebarHTML" && !(args.sidebars || args.sidebars_only))
+}
+
+/// Report an array entry (e.g. a body block) that only exists on one side.
+fn block_diff(
+    lhs: Option<&Value>,
+    rhs: Option<&Value>,
+    file: &str,
+    path: &[PathIndex],
+    diff: &mut BTreeMap<String, String>,
+    allowlisted: &mut usize,
+    args: &BuildArgs,
+) {
+    let key = make_key(path);
+    let (side, block) = match (lhs, rhs) {
+        (Some(lhs), None) => ("A", lhs),
+        (None, Some(rhs)) => ("B", rhs),
+        _ => return,
+    };
+    let (lhs, rhs) = (lhs.unwrap_or(&Value::Null), rhs.unwrap_or(&Value::Null));
+    if skip_path(lhs, rhs, file, path, &key, allowlisted, args) {
+        return;
+    }
+    let content = match block {
+        Value::String(s) if is_html(s) => normalize_html(s, args),
+        _ if args.dts_only => return,
+        Value::String(s) => s.clone(),
+        block => serde_json::to_string_pretty(block).unwrap_or_default(),
+    };
+    // Compared as JSON, so a block never shares a diff with an emptied string.
+    let text = Value::String(content.clone()).to_string();
+    let (lhs, rhs) = if side == "A" {
+        (text.as_str(), "null")
+    } else {
+        ("null", text.as_str())
+    };
+    record_diff(file, key, (lhs, rhs), diff, args, || {
+        format!("block only in {side}:\n{content}")
+    });
+}
+
+/// `html` as compared: only its `<dl>`s with `--dts-only`, only its text with `--text-only`,
+/// and massaged and formatted otherwise.
+fn normalize_html(html: &str, args: &BuildArgs) -> String {
+    let html = if args.dts_only {
+        extract_dls(html)
+    } else {
+        html.to_string()
+    };
+    if args.text_only {
+        text_content(&html).expect("html processing failed")
+    } else {
+        fmt_html(&massage_html(&html, args).expect("html processing failed"))
+    }
+}
+
+/// Record that `lhs` and `rhs` differ at `key` of `file`, for all enabled outputs.
+///
+/// The diff made by `render` is kept in [`DIFF_MAP`], computed once per distinct pair,
+/// and `diff` gets a placeholder for it.
+fn record_diff(
+    file: &str,
+    key: String,
+    (lhs, rhs): (&str, &str),
+    diff: &mut BTreeMap<String, String>,
+    args: &BuildArgs,
+    render: impl FnOnce() -> String,
+) {
+    let diff_hash = diff_hash(lhs, rhs);
+    if args.sort_by == SortBy::Size {
+        DIFF_SIZES.insert((file.to_string(), key.clone()), changed_chars(lhs, rhs));
+    }
+    if args.fingerprint {
+        DIFF_HASHES.insert((file.to_string(), key.clone()), diff_hash.clone());
+    }
+    if args.summary_only {
+        diff.insert(key, String::new());
         return;
     }
+    if !DIFF_MAP.contains_key(&diff_hash) {
+        DIFF_MAP.insert(diff_hash.clone(), render());
+    }
+    diff.insert(key, format!("{REF_PLACEHOLDER}{diff_hash}"));
+}
+
+/// Schema keys of one file for `--report-schema-additions`, with array indices as `*`.
+#[derive(Default)]
+struct SchemaKeys {
+    /// Keys of objects found on both sides.
+    both: HashSet<String>,
+    /// Keys only present on one side, with that side.
+    one_sided: HashSet<(String, &'static str)>,
+}
+
+fn schema_keys(lhs: &Value, rhs: &Value, path: &str, keys: &mut SchemaKeys) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (lhs, rhs) {
+        (Value::Object(lhs), Value::Object(rhs)) => {
+            for (key, value) in lhs {
+                let path = join(key);
+                match rhs.get(key) {
+                    Some(other) => {
+                        schema_keys(value, other, &path, keys);
+                        keys.both.insert(path);
+                    }
+                    None => {
+                        keys.one_sided.insert((path, "A"));
+                    }
+                }
+            }
+            for key in rhs.keys().filter(|key| !lhs.contains_key(*key)) {
+                keys.one_sided.insert((join(key), "B"));
+            }
+        }
+        (Value::Array(lhs), Value::Array(rhs)) => {
+            let path = join("*");
+            for (lhs, rhs) in lhs.iter().zip(rhs) {
+                schema_keys(lhs, rhs, &path, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keys present on only one side, counted once per file, most widespread first.
+///
+/// A deliberate schema addition shows up in (almost) all files having the key on either side,
+/// a per-page content difference in a few.
+fn schema_additions(a: &BTreeMap<String, Value>, b: &BTreeMap<String, Value>) -> String {
+    let per_file = a
+        .par_iter()
+        .filter_map(|(k, v)| b.get(k).map(|right| (v, right)))
+        .map(|(lhs, rhs)| {
+            let mut keys = SchemaKeys::default();
+            schema_keys(lhs, rhs, "", &mut keys);
+            keys
+        })
+        .collect::<Vec<_>>();
+    let mut both = HashMap::<&str, usize>::new();
+    let mut one_sided = BTreeMap::<(&str, &str), usize>::new();
+    for keys in &per_file {
+        for key in &keys.both {
+            *both.entry(key.as_str()).or_default() += 1;
+        }
+        for (key, side) in &keys.one_sided {
+            *one_sided.entry((key.as_str(), side)).or_default() += 1;
+        }
+    }
+    let mut one_sided = one_sided.into_iter().collect::<Vec<_>>();
+    // Stable sort, so equally widespread keys stay in key order.
+    one_sided.sort_by(|(_, a), (_, b)| b.cmp(a));
+    one_sided
+        .into_iter()
+        .map(|((key, side), files)| {
+            let with_key = files + both.get(key).copied().unwrap_or_default();
+            format!("only in {side}: {key} - {files} of {with_key} files\n")
+        })
+        .collect()
+}
+
+/// Keys identifying an object within an array, tried in order for `--unordered-arrays`.
+const IDENTITY_KEYS: &[&str] = &["id", "url", "slug", "name", "title"];
+
+/// Both arrays sorted, if they can be compared as multisets: all elements are scalars, or
+/// all are objects sharing one of the [`IDENTITY_KEYS`]. Objects are sorted by that key, so
+/// the same entries line up even if their contents differ.
+fn sort_unordered(lhs: &[Value], rhs: &[Value]) -> Option<(Vec<Value>, Vec<Value>)> {
+    let all = || lhs.iter().chain(rhs);
+    let id = if all().all(|v| !v.is_array() && !v.is_object()) {
+        None
+    } else {
+        Some(
+            IDENTITY_KEYS
+                .iter()
+                .find(|id| all().all(|v| v.get(id).is_some()))?,
+        )
+    };
+    let sort_key = |v: &Value| match id {
+        Some(id) => v[id].to_string(),
+        None => v.to_string(),
+    };
+    let sorted = |values: &[Value]| {
+        let mut values = values.to_vec();
+        values.sort_by_cached_key(&sort_key);
+        values
+    };
+    Some((sorted(lhs), sorted(rhs)))
+}
 
-    if ALLOWLIST.contains(&(file, &key)) {
+fn full_diff(
+    lhs: &Value,
+    rhs: &Value,
+    file: &str,
+    path: &[PathIndex],
+    diff: &mut BTreeMap<String, String>,
+    allowlisted: &mut usize,
+    args: &BuildArgs,
+) {
+    let key = make_key(path);
+    if skip_path(lhs, rhs, file, path, &key, allowlisted, args) {
         return;
     }
 
     if lhs != rhs {
-        if IGNORED_KEYS.iter().any(|i| key.starts_with(i))
-            || key == "doc.sidebarHTML" && !args.sidebars
-        {
-            return;
-        }
         match (lhs, rhs) {
             (Value::Array(lhs), Value::Array(rhs)) => {
                 let len = max(lhs.len(), rhs.len());
+                let unordered;
                 let (lhs, rhs) = if key.ends_with("specifications") {
                     // sort specs by `bcdSpecificationURL` to make the diff more stable
-                    // example docs/web/mathml/global_attributes/index.json
+                    // example en-us/docs/web/mathml/global_attributes/index.json
                     let mut lhs_sorted = lhs.clone();
                     let mut rhs_sorted = rhs.clone();
                     lhs_sorted.sort_by_key(|v| {
@@ -274,20 +1132,25 @@ fn full_diff(
                             .to_string()
                     });
                     (&lhs_sorted.clone(), &lhs_sorted.clone())
+                } else if let Some(sorted) = args
+                    .unordered_arrays
+                    .then(|| sort_unordered(lhs, rhs))
+                    .flatten()
+                {
+                    unordered = sorted;
+                    (&unordered.0, &unordered.1)
                 } else {
                     (lhs, rhs)
                 };
                 for i in 0..len {
                     let mut path = path.to_vec();
                     path.push(PathIndex::Array(i));
-                    full_diff(
-                        lhs.get(i).unwrap_or(&Value::Null),
-                        rhs.get(i).unwrap_or(&Value::Null),
-                        file,
-                        &path,
-                        diff,
-                        args,
-                    );
+                    match (lhs.get(i), rhs.get(i)) {
+                        (Some(lhs), Some(rhs)) => {
+                            full_diff(lhs, rhs, file, &path, diff, allowlisted, args)
+                        }
+                        (lhs, rhs) => block_diff(lhs, rhs, file, &path, diff, allowlisted, args),
+                    }
                 }
             }
             (Value::Object(lhs), Value::Object(rhs)) => {
@@ -302,6 +1165,7 @@ fn full_diff(
                         file,
                         &path,
                         diff,
+                        allowlisted,
                         args,
                     );
                 }
@@ -328,59 +1192,38 @@ fn full_diff(
                     }
                     _ => {}
                 };
-                if is_html(&lhs) && is_html(&rhs) {
-                    let lhs_t = WS_DIFF.replace_all(&lhs, "$x$y");
-                    let rhs_t = WS_DIFF.replace_all(&rhs, "$x$y");
-                    let lhs_t = EMPTY_P_DIFF.replace_all(&lhs_t, "");
-                    let rhs_t = EMPTY_P_DIFF.replace_all(&rhs_t, "");
-                    let lhs_t = rewrite_str(
-                        &lhs_t,
-                        RewriteStrSettings {
-                            element_content_handlers: pre_diff_element_massaging_handlers(args),
-                            ..RewriteStrSettings::new()
-                        },
-                    )
-                    .expect("lolhtml processing failed");
-                    let rhs_t = rewrite_str(
-                        &rhs_t,
-                        RewriteStrSettings {
-                            element_content_handlers: pre_diff_element_massaging_handlers(args),
-                            ..RewriteStrSettings::new()
-                        },
-                    )
-                    .expect("lolhtml processing failed");
-                    lhs = fmt_html(&html_minifier::minify(lhs_t).unwrap());
-                    rhs = fmt_html(&html_minifier::minify(rhs_t).unwrap());
+                if args.dts_only && !(is_html(&lhs) && is_html(&rhs)) {
+                    return;
+                }
+                let html = is_html(&lhs) && is_html(&rhs);
+                if html {
+                    lhs = normalize_html(&lhs, args);
+                    rhs = normalize_html(&rhs, args);
                 }
                 if lhs != rhs {
-                    let mut diff_hash = Sha256::new();
-                    diff_hash.write_all(lhs.as_bytes()).unwrap();
-                    diff_hash.write_all(rhs.as_bytes()).unwrap();
-                    let diff_hash = BASE64_STANDARD_NO_PAD.encode(&diff_hash.finalize()[..]);
-                    if let Some(hash) = DIFF_MAP.get(&diff_hash) {
-                        diff.insert(key, format!("See {}", hash.as_str()));
-                        return;
+                    if args.tag_stats && html && !args.text_only {
+                        DIFF_ELEMENTS.insert(
+                            (file.to_string(), key.clone()),
+                            changed_elements(&lhs, &rhs),
+                        );
                     }
-                    DIFF_MAP.insert(diff_hash, "somewhere else".into());
-                    diff.insert(
-                        key,
-                        ansi_to_html::convert(&if args.fast {
+                    record_diff(file, key, (&lhs, &rhs), diff, args, || {
+                        if args.fast {
                             diff_lines(&lhs, &rhs).to_string()
                         } else {
                             diff_words(&lhs, &rhs).to_string()
-                        })
-                        .unwrap(),
-                    );
+                        }
+                    });
                 }
             }
+            (_, _) if args.dts_only => {}
             (lhs, rhs) => {
                 let lhs = lhs.to_string();
                 let rhs = rhs.to_string();
                 if lhs != rhs {
-                    diff.insert(
-                        key,
-                        ansi_to_html::convert(&diff_words(&lhs, &rhs).to_string()).unwrap(),
-                    );
+                    record_diff(file, key, (&lhs, &rhs), diff, args, || {
+                        diff_words(&lhs, &rhs).to_string()
+                    });
                 }
             }
         }
@@ -392,142 +1235,693 @@ fn main() -> Result<(), anyhow::Error> {
 
     match &cli.command {
         Commands::Diff(arg) => {
-            println!("Gathering everything 🧺");
-            let start = std::time::Instant::now();
-            let a = gather(&arg.root_a, arg.query.as_deref())?;
-            let b = gather(&arg.root_b, arg.query.as_deref())?;
-
-            let hits = max(a.len(), b.len());
-            let same = AtomicUsize::new(0);
-            if arg.html {
-                let list_items = a.par_iter().filter_map(|(k, v)| {
-                    if b.get(k) == Some(v) {
-                        same.fetch_add(1, Relaxed);
-                        return None;
-                    }
+            let roots = [arg.root_a.as_path(), arg.root_b.as_path()];
+            let ignore_file = arg.ignore_file.as_deref();
+            if let Some(report) = &arg.since_report {
+                let _ = KNOWN_DIFFS.set(parse_csv_report(&fs::read_to_string(report)?));
+            }
+            let mut last = snapshot(&roots, ignore_file);
+            run_diff(arg)?;
+            if arg.watch {
+                loop {
+                    println!("Watching for changes 👀");
+                    last = wait_for_change(
+                        &roots,
+                        ignore_file,
+                        last,
+                        Duration::from_secs(arg.watch_interval),
+                    );
+                    DIFF_MAP.clear();
+                    DIFF_HASHES.clear();
+                    DIFF_SIZES.clear();
+                    DIFF_ELEMENTS.clear();
+                    FILE_ALLOWLIST_HIDDEN.clear();
+                    run_diff(arg)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
-                    if arg.value {
-                        let left = v;
-                        let right = b.get(k).unwrap_or(&Value::Null);
-                        let mut diff = BTreeMap::new();
-                        full_diff(left, right, k, &[], &mut diff, arg);
-                        if !diff.is_empty() {
-                            return Some((k.clone(), format!(
-                                r#"<li><span>{k}</span><div class="r"><pre><code>{}</code></pre></div></li>"#,
-                                serde_json::to_string_pretty(&diff).unwrap_or_default(),
-                            )));
-                        } else {
+/// A progress bar for a diff pass over `len` files, cleared once the pass is done.
+fn progress_bar(arg: &BuildArgs, len: usize) -> ProgressBar {
+    if arg.no_progress || !std::io::stdout().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    ProgressBar::new(len as u64)
+        .with_style(
+            ProgressStyle::with_template("{bar:40} {pos}/{len} files, ETA {eta}")
+                .expect("invalid progress template"),
+        )
+        .with_finish(ProgressFinish::AndClear)
+}
+
+/// The --list-results line for file `k` with `n` differences.
+fn result_line(k: &str, n: usize) -> String {
+    if n == 0 {
+        format!("PASS {k}")
+    } else {
+        format!("FAIL {k} {n}")
+    }
+}
+
+/// The paths listed for --include, either one per line or as `PASS`/`FAIL` lines
+/// printed by --list-results.
+fn parse_include(list: &str) -> HashSet<String> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once(' ') {
+            Some(("PASS" | "FAIL", rest)) => rest.split(' ').next().unwrap_or_default(),
+            _ => line,
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+fn run_diff(arg: &BuildArgs) -> Result<(), Error> {
+    println!("Gathering everything 🧺");
+    let start = std::time::Instant::now();
+    let changed = arg
+        .changed_since
+        .as_deref()
+        .map(changed_since)
+        .transpose()?;
+    let included = arg
+        .include
+        .as_deref()
+        .map(|path| -> Result<HashSet<String>, Error> {
+            Ok(parse_include(&fs::read_to_string(path)?))
+        })
+        .transpose()?;
+    let changed = match (changed, included) {
+        (Some(changed), Some(included)) => Some(&changed & &included),
+        (changed, included) => changed.or(included),
+    };
+    let gather_local = |root: &Path| {
+        gather(
+            root,
+            &arg.query,
+            arg.ignore_file.as_deref(),
+            changed.as_ref(),
+            &arg.locales,
+        )
+    };
+    let gather_remote = |root: &Path, local: &BTreeMap<String, Value>| {
+        let keys = local.keys().cloned().collect::<HashSet<_>>();
+        gather(root, &arg.query, None, Some(&keys), &[])
+    };
+    let (a, b) = match (remote_base(&arg.root_a), remote_base(&arg.root_b)) {
+        (Some(_), Some(_)) => return Err(anyhow!("only one root can be a url")),
+        (Some(_), None) => {
+            let b = gather_local(&arg.root_b)?;
+            (gather_remote(&arg.root_a, &b)?, b)
+        }
+        (None, Some(_)) => {
+            let a = gather_local(&arg.root_a)?;
+            let b = gather_remote(&arg.root_b, &a)?;
+            (a, b)
+        }
+        (None, None) => (gather_local(&arg.root_a)?, gather_local(&arg.root_b)?),
+    };
+
+    if arg.validate_html {
+        let mut out = vec!["Root;File;JSON Path;Error\n".to_string()];
+        for (root, docs) in [("a", &a), ("b", &b)] {
+            out.extend(
+                docs.par_iter()
+                    .flat_map_iter(|(k, v)| {
+                        let mut errors = vec![];
+                        validate_html(v, &[], &mut errors, arg);
+                        errors
+                            .into_iter()
+                            .map(move |(path, e)| format!("{root};{k};{path};{e}\n"))
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let mut file = File::create(&arg.out)?;
+        file.write_all(out.concat().as_bytes())?;
+        println!(
+            "Took: {:?} - {} malformed html values",
+            start.elapsed(),
+            out.len() - 1
+        );
+        return Ok(());
+    }
+
+    if arg.list_results {
+        let mut results = a
+            .par_iter()
+            .progress_with(progress_bar(arg, a.len()))
+            .map(|(k, v)| {
+                let right = b.get(k).unwrap_or(&Value::Null);
+                let mut diff = BTreeMap::new();
+                if v != right {
+                    full_diff(v, right, k, &[], &mut diff, &mut 0, arg);
+                }
+                (k.as_str(), diff.len())
+            })
+            .collect::<Vec<_>>();
+        results.sort();
+        for (k, n) in results {
+            println!("{}", result_line(k, n));
+        }
+        return Ok(());
+    }
+
+    if arg.report_schema_additions {
+        print!("{}", schema_additions(&a, &b));
+        println!("Took: {:?}", start.elapsed());
+        return Ok(());
+    }
+
+    if arg.summary_only {
+        let diffs = a
+            .par_iter()
+            .progress_with(progress_bar(arg, a.len()))
+            .filter_map(|(k, v)| {
+                let right = b.get(k).unwrap_or(&Value::Null);
+                let mut diff = BTreeMap::new();
+                if v != right {
+                    full_diff(v, right, k, &[], &mut diff, &mut 0, arg);
+                }
+                (!diff.is_empty()).then(|| (category(k), diff.len()))
+            })
+            .collect::<Vec<_>>();
+        let mut by_category = BTreeMap::<String, (usize, usize)>::new();
+        for (category, paths) in diffs {
+            let entry = by_category.entry(category).or_default();
+            entry.0 += 1;
+            entry.1 += paths;
+        }
+        let (mut files, mut paths) = (0, 0);
+        for (category, (category_files, category_paths)) in by_category {
+            println!("{category}: {category_files} files, {category_paths} paths");
+            files += category_files;
+            paths += category_paths;
+        }
+        println!(
+            "Took: {:?} - {files} files with {paths} differing paths, of {}",
+            start.elapsed(),
+            max(a.len(), b.len())
+        );
+        return Ok(());
+    }
+
+    let hits = max(a.len(), b.len());
+    let same = AtomicUsize::new(0);
+    // Files counted as ok only because the allowlist suppressed their diffs.
+    let via_allowlist = AtomicUsize::new(0);
+    // Diff every file once, all outputs reporting json paths read from this.
+    let diffed =
+        arg.html && arg.value || arg.csv || arg.junit.is_some() || arg.fingerprint || arg.tag_stats;
+    let results = if diffed {
+        a.par_iter()
+            .progress_with(progress_bar(arg, a.len()))
+            .map(|(k, v)| {
+                let right = b.get(k).unwrap_or(&Value::Null);
+                let mut diff = BTreeMap::new();
+                let mut allowlisted = 0;
+                if v != right {
+                    full_diff(v, right, k, &[], &mut diff, &mut allowlisted, arg);
+                }
+                if diff.is_empty() {
+                    same.fetch_add(1, Relaxed);
+                    if allowlisted > 0 {
+                        via_allowlist.fetch_add(1, Relaxed);
+                    }
+                }
+                (k.clone(), diff)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+    if arg.html {
+        let sources = [source_base(&arg.root_a), source_base(&arg.root_b)];
+        let list_items = if arg.value {
+            let mut rendered = results.clone();
+            resolve_refs(&mut rendered, arg.max_diff_render);
+            rendered
+                .into_iter()
+                .filter(|(_, diff)| !diff.is_empty())
+                .map(|(k, diff)| {
+                    let size = diff
+                        .keys()
+                        .filter_map(|path| {
+                            DIFF_SIZES.get(&(k.clone(), path.clone())).map(|size| *size)
+                        })
+                        .sum::<usize>();
+                    let li = format!(
+                        r#"<li>{}<div class="r"><pre><code>{}</code></pre></div></li>"#,
+                        item_header(&k, &sources),
+                        serde_json::to_string_pretty(&diff).unwrap_or_default(),
+                    );
+                    (k, size, li)
+                })
+                .collect::<Vec<_>>()
+        } else {
+            a.par_iter()
+                .progress_with(progress_bar(arg, a.len()))
+                .filter_map(|(k, v)| {
+                    // Files are only counted here if they weren't diffed above already.
+                    if b.get(k) == Some(v) {
+                        if !diffed {
                             same.fetch_add(1, Relaxed);
                         }
-                        None
+                        return None;
+                    }
+                    let left = &v.as_str().unwrap_or_default();
+                    let right = b
+                        .get(k)
+                        .unwrap_or(&Value::Null)
+                        .as_str()
+                        .unwrap_or_default();
+                    let htmls = if arg.ignore_html_whitespace {
+                        let left_html =
+                            html_minifier::minify(WS_DIFF.replace_all(left, "$x$y")).unwrap();
+                        let right_html =
+                            html_minifier::minify(WS_DIFF.replace_all(right, "$x$y")).unwrap();
+                        Some((left_html, right_html))
                     } else {
-                        let left = &v.as_str().unwrap_or_default();
-                        let right = b
-                            .get(k)
-                            .unwrap_or(&Value::Null)
-                            .as_str()
-                            .unwrap_or_default();
-                        let htmls = if arg.ignore_html_whitespace {
-                            let left_html =
-                                html_minifier::minify(WS_DIFF.replace_all(left, "$x$y")).unwrap();
-                            let right_html =
-                                html_minifier::minify(WS_DIFF.replace_all(right, "$x$y")).unwrap();
-                            Some((left_html, right_html))
-                        } else {
-                            None
-                        };
-
-                        let (left, right) = htmls
-                            .as_ref()
-                            .map(|(l, r)| (l.as_str(), r.as_str()))
-                            .unwrap_or((left, right));
-                        if left == right {
-                            println!("only broken links differ");
+                        None
+                    };
+
+                    let (left, right) = htmls
+                        .as_ref()
+                        .map(|(l, r)| (l.as_str(), r.as_str()))
+                        .unwrap_or((left, right));
+                    if left == right {
+                        println!("only broken links differ");
+                        if !diffed {
                             same.fetch_add(1, Relaxed);
-                            return None;
-                        }
-                        if arg.inline {
-                            println!("{}", diff_words(left, right));
                         }
-                        Some((k.clone(), format!(
-                    r#"<li><span>{k}</span><div class="a">{}</div><div class="b">{}</div></li>"#,
-                    left, right
-                )))
+                        return None;
                     }
-                }
-                ).collect::<Vec<_>>();
-                let out: BTreeMap<String, Vec<_>> =
-                    list_items
-                        .into_iter()
-                        .fold(BTreeMap::new(), |mut acc, (k, li)| {
-                            let p = k.splitn(4, '/').collect::<Vec<_>>();
-                            let cat = match &p[..] {
-                                ["docs", "web", cat, ..] => format!("docs/web/{cat}"),
-                                ["docs", cat, ..] => format!("docs/{cat}"),
-                                [cat, ..] => cat.to_string(),
-                                [] => "".to_string(),
-                            };
-                            acc.entry(cat).or_default().push(li);
-                            acc
-                        });
-
-                let out = out.into_iter().fold(String::new(), |mut acc, (k, v)| {
-                    write!(
-                        acc,
-                        r#"<li><details><summary>[{}] {k}</summary><ul>{}</ul></details></li>"#,
-                        v.len(),
-                        v.into_iter().collect::<String>(),
-                    )
-                    .unwrap();
+                    if arg.inline {
+                        println!("{}", diff_words(left, right));
+                    }
+                    Some((
+                        k.clone(),
+                        0,
+                        format!(
+                            r#"<li>{}<div class="a">{}</div><div class="b">{}</div></li>"#,
+                            item_header(k, &sources),
+                            left,
+                            right
+                        ),
+                    ))
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut out: BTreeMap<String, Vec<_>> =
+            list_items
+                .into_iter()
+                .fold(BTreeMap::new(), |mut acc, (k, size, li)| {
+                    acc.entry(category(&k)).or_default().push((k, size, li));
                     acc
                 });
-                let file = File::create(&arg.out)?;
-                let mut buffer = BufWriter::new(file);
-
-                buffer.write_all(html(&out).as_bytes())?;
+        for v in out.values_mut() {
+            // Don't rely on the parallel collect for the order, so runs over the same
+            // inputs produce identical reports.
+            v.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+            if arg.sort_by == SortBy::Size {
+                // Stable sort, so equally sized entries stay in path order.
+                v.sort_by(|(_, a, _), (_, b, _)| b.cmp(a));
             }
-            if arg.csv {
-                let mut out = Vec::new();
-                out.push("File;JSON Path\n".to_string());
-                out.extend(
-                    a.par_iter()
-                        .filter_map(|(k, v)| {
-                            if b.get(k) == Some(v) {
-                                same.fetch_add(1, Relaxed);
-                                return None;
-                            }
-
-                            let left = v;
-                            let right = b.get(k).unwrap_or(&Value::Null);
-                            let mut diff = BTreeMap::new();
-                            full_diff(left, right, k, &[], &mut diff, arg);
-                            if !diff.is_empty() {
-                                return Some(format!(
-                                    "{}\n",
-                                    diff.into_keys()
-                                        .map(|jsonpath| format!("{};{}", k, jsonpath))
-                                        .collect::<Vec<_>>()
-                                        .join("\n")
-                                ));
-                            } else {
-                                same.fetch_add(1, Relaxed);
-                            }
-                            None
-                        })
-                        .collect::<Vec<_>>(),
-                );
-                let mut file = File::create(&arg.out)?;
+        }
 
-                file.write_all(out.into_iter().collect::<String>().as_bytes())?;
+        if arg.split_by_category {
+            fs::create_dir_all(&arg.out)?;
+            let mut index = String::new();
+            for (k, v) in out {
+                let path = arg.out.join(format!("{k}.html"));
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                write!(
+                    index,
+                    r#"<li><a href="{k}.html">[{}] {k}</a></li>"#,
+                    v.len()
+                )
+                .unwrap();
+                let mut acc = String::new();
+                write_category(&mut acc, &k, v);
+                fs::write(&path, html(&acc))?;
             }
+            fs::write(arg.out.join("index.html"), html(&index))?;
+        } else {
+            let out = out.into_iter().fold(String::new(), |mut acc, (k, v)| {
+                write_category(&mut acc, &k, v);
+                acc
+            });
+            let file = File::create(&arg.out)?;
+            let mut buffer = BufWriter::new(file);
 
-            println!(
-                "Took: {:?} - {}/{hits} ok, {} remaining",
-                start.elapsed(),
-                same.load(Relaxed),
-                hits - same.load(Relaxed)
-            );
+            buffer.write_all(html(&out).as_bytes())?;
         }
     }
+    if arg.csv {
+        let mut out = String::from("File;JSON Path\n");
+        for (k, diff) in &results {
+            for jsonpath in diff.keys() {
+                writeln!(out, "{k};{jsonpath}").unwrap();
+            }
+        }
+        fs::write(&arg.out, out)?;
+    }
+
+    if let Some(junit) = &arg.junit {
+        let mut plain = results.clone();
+        resolve_plain(&mut plain);
+        write_junit(junit, &plain, start.elapsed())?;
+    }
+
+    if let Some(treemap) = &arg.treemap {
+        let counts = results
+            .iter()
+            .map(|(k, diff)| (k.clone(), diff.len()))
+            .collect::<Vec<_>>();
+        write_treemap(treemap, &counts)?;
+    }
+
+    if arg.fingerprint {
+        println!("Fingerprint: {}", fingerprint());
+    }
+    if arg.tag_stats {
+        println!("Elements in diffs: {}", tag_stats());
+    }
+
+    println!(
+        "Took: {:?} - {}/{hits} ok ({} via allowlist), {} remaining",
+        start.elapsed(),
+        same.load(Relaxed),
+        via_allowlist.load(Relaxed),
+        hits - same.load(Relaxed)
+    );
+    let mut hidden = FILE_ALLOWLIST_HIDDEN
+        .iter()
+        .map(|entry| (entry.key().clone(), *entry.value()))
+        .collect::<Vec<_>>();
+    hidden.sort();
+    for (file, count) in hidden {
+        println!("  {file}: {count} diffs hidden by the file allowlist");
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_void_elements() {
+        assert_eq!(
+            normalize_void_elements(r#"<p>a<br>b<br/>c<br /></p>"#).unwrap(),
+            "<p>a<br/>b<br/>c<br/></p>"
+        );
+        assert_eq!(
+            normalize_void_elements(r#"<img src=a.png alt='a b'>"#).unwrap(),
+            r#"<img src="a.png" alt="a b"/>"#
+        );
+        assert_eq!(normalize_void_elements("<p>a</p>").unwrap(), "<p>a</p>");
+    }
+
+    #[test]
+    fn test_category() {
+        assert_eq!(
+            category("en-us/docs/web/css/color/index.json"),
+            "en-us/docs/web/css"
+        );
+        assert_eq!(
+            category("fr/docs/glossary/html/index.json"),
+            "fr/docs/glossary"
+        );
+        assert_eq!(category("en-us/blog/index.json"), "en-us/blog");
+        assert_eq!(category("docs/web/css/color/index.json"), "docs/web/css");
+        assert_eq!(category("docs/glossary/html/index.json"), "docs/glossary");
+        assert_eq!(category("index.json"), "index.json");
+    }
+
+    #[test]
+    fn test_gather_locales() -> Result<(), Error> {
+        let root = std::env::temp_dir().join(format!("diff-test-locales-{}", std::process::id()));
+        for locale in ["en-us", "fr", "de"] {
+            let dir = root.join(locale).join("docs/web");
+            fs::create_dir_all(&dir)?;
+            fs::write(
+                dir.join("index.json"),
+                format!(r#"{{"locale":"{locale}"}}"#),
+            )?;
+        }
+        let keys = |locales: &[&str]| -> Result<Vec<String>, Error> {
+            let locales = locales.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+            Ok(gather(&root, &[], None, None, &locales)?
+                .into_keys()
+                .collect())
+        };
+        let result = (|| -> Result<(), Error> {
+            // A single locale folder keeps its old keys, but can't be filtered by locale.
+            let fr = root.join("fr");
+            assert_eq!(
+                gather(&fr, &[], None, None, &[])?
+                    .into_keys()
+                    .collect::<Vec<_>>(),
+                ["docs/web/index.json"]
+            );
+            assert!(gather(&fr, &[], None, None, &["fr".to_string()]).is_err());
+            assert_eq!(keys(&["fr"])?, ["fr/docs/web/index.json"]);
+            assert_eq!(
+                keys(&["FR", "en-US"])?,
+                ["en-us/docs/web/index.json", "fr/docs/web/index.json"]
+            );
+            assert_eq!(keys(&[])?.len(), 3);
+            Ok(())
+        })();
+        fs::remove_dir_all(&root)?;
+        result
+    }
+
+    #[test]
+    fn test_in_locales() {
+        let locales = ["fr".to_string(), "en-US".to_string()];
+        assert!(in_locales("fr/docs/web/index.json", &locales));
+        assert!(in_locales("en-us/docs/web/index.json", &locales));
+        assert!(!in_locales("de/docs/web/index.json", &locales));
+        assert!(!in_locales("index.json", &locales));
+        assert!(in_locales("de/docs/web/index.json", &[]));
+    }
+
+    #[test]
+    fn test_is_ignored_key() {
+        let ignored = &["doc.summary", "doc.flaws.*"];
+        assert!(is_ignored_key("doc.summary", ignored));
+        assert!(!is_ignored_key("doc.summaryExtra", ignored));
+        assert!(!is_ignored_key("doc.summary.0", ignored));
+        assert!(is_ignored_key("doc.flaws", ignored));
+        assert!(is_ignored_key("doc.flaws.broken_links.0", ignored));
+        assert!(!is_ignored_key("doc.flawsCount", ignored));
+    }
+
+    #[test]
+    fn test_is_scoped_key() {
+        let rules = &[
+            ("", "doc.baseline"),
+            ("en-us/docs/web/html/element/input/", "doc.body.*"),
+        ];
+        assert!(is_scoped_key(
+            "en-us/docs/web/css/index.json",
+            "doc.baseline",
+            rules
+        ));
+        assert!(is_scoped_key(
+            "en-us/docs/web/html/element/input/text/index.json",
+            "doc.body.0.value.content",
+            rules
+        ));
+        assert!(!is_scoped_key(
+            "en-us/docs/web/html/element/select/index.json",
+            "doc.body.0.value.content",
+            rules
+        ));
+    }
+
+    #[test]
+    fn test_parse_csv_report() {
+        let known = parse_csv_report(
+            "File;JSON Path\n\
+             docs/web/index.json;doc.body.0.value.content\n\
+             docs/web/index.json;doc.title\n",
+        );
+        assert_eq!(known.len(), 1);
+        assert!(known["docs/web/index.json"].contains("doc.title"));
+        assert!(known["docs/web/index.json"].contains("doc.body.0.value.content"));
+    }
+
+    #[test]
+    fn test_schema_additions() {
+        let a = BTreeMap::from([
+            (
+                "a.json".to_string(),
+                json!({ "doc": { "title": "A", "toc": [{ "id": "x" }] } }),
+            ),
+            (
+                "b.json".to_string(),
+                json!({ "doc": { "title": "B", "toc": [] } }),
+            ),
+        ]);
+        let b = BTreeMap::from([
+            (
+                "a.json".to_string(),
+                json!({ "doc": { "title": "A", "new": 1, "toc": [{ "id": "x", "text": "X" }] } }),
+            ),
+            (
+                "b.json".to_string(),
+                json!({ "doc": { "new": 2, "toc": [] } }),
+            ),
+        ]);
+        assert_eq!(
+            schema_additions(&a, &b),
+            "only in B: doc.new - 2 of 2 files\n\
+             only in A: doc.title - 1 of 2 files\n\
+             only in B: doc.toc.*.text - 1 of 1 files\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_unordered() {
+        let (lhs, rhs) = sort_unordered(&[json!("b"), json!(1)], &[json!(1), json!("b")]).unwrap();
+        assert_eq!(lhs, rhs);
+
+        let lhs = [json!({ "url": "/b", "title": "B" }), json!({ "url": "/a" })];
+        let rhs = [
+            json!({ "url": "/a" }),
+            json!({ "url": "/b", "title": "Bee" }),
+        ];
+        let (lhs, rhs) = sort_unordered(&lhs, &rhs).unwrap();
+        assert_eq!(lhs[0], json!({ "url": "/a" }));
+        assert_eq!(rhs[0], json!({ "url": "/a" }));
+        assert_eq!(rhs[1], json!({ "url": "/b", "title": "Bee" }));
+
+        assert!(sort_unordered(&[json!({ "url": "/a" })], &[json!({ "id": "a" })]).is_none());
+        assert!(sort_unordered(&[json!([1])], &[json!([1])]).is_none());
+    }
+
+    #[test]
+    fn test_changed_chars() {
+        assert_eq!(changed_chars("<p>a b</p>", "<p>a b</p>"), 0);
+        assert_eq!(changed_chars("one two three", "one 2 three"), 4);
+        assert_eq!(changed_chars("\"abc\"", "\"abcd\""), 11);
+    }
+
+    #[test]
+    fn test_truncate_diff() {
+        let mut diff = "\x1b[31mabc\x1b[0mdef".to_string();
+        truncate_diff(&mut diff, 4);
+        assert_eq!(
+            diff,
+            "\x1b[31mabc\x1b[0md\x1b[0m… (truncated, 2 more chars)"
+        );
+        let mut diff = "\x1b[31mabc\x1b[0m".to_string();
+        truncate_diff(&mut diff, 3);
+        assert_eq!(diff, "\x1b[31mabc\x1b[0m");
+    }
+
+    #[test]
+    fn test_resolve_refs() {
+        DIFF_MAP.insert("test-shared".to_string(), "shared diff".to_string());
+        DIFF_MAP.insert("test-single".to_string(), "single diff".to_string());
+        let placeholder = |hash: &str| format!("{REF_PLACEHOLDER}{hash}");
+        let mut results = vec![
+            (
+                "b/index.json".to_string(),
+                BTreeMap::from([("doc.body".to_string(), placeholder("test-shared"))]),
+            ),
+            (
+                "a/index.json".to_string(),
+                BTreeMap::from([
+                    ("doc.body".to_string(), placeholder("test-single")),
+                    ("doc.toc".to_string(), placeholder("test-shared")),
+                ]),
+            ),
+        ];
+        resolve_refs(&mut results, None);
+        assert_eq!(results[0].0, "a/index.json");
+        assert_eq!(results[0].1["doc.body"], "single diff");
+        assert_eq!(results[0].1["doc.toc"], "ref-1: shared diff");
+        assert_eq!(results[1].1["doc.body"], "See ref-1");
+    }
+
+    #[test]
+    fn test_include_round_trip() {
+        let listed = [
+            result_line("en-us/docs/web/a/index.json", 0),
+            result_line("fr/docs/web/b/index.json", 3),
+        ]
+        .join("\n");
+        let expected = ["en-us/docs/web/a/index.json", "fr/docs/web/b/index.json"]
+            .map(str::to_string)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(parse_include(&listed), expected);
+        assert_eq!(
+            parse_include("en-us/docs/web/a/index.json\n\n fr/docs/web/b/index.json \n"),
+            expected
+        );
+    }
+
+    /// Args for `diff-test diff` with the extra `flags`.
+    fn build_args(flags: &[&str]) -> BuildArgs {
+        let Commands::Diff(args) = Cli::parse_from(
+            ["diff-test", "diff", "-o", "out", "a", "b"]
+                .iter()
+                .chain(flags),
+        )
+        .command;
+        args
+    }
+
+    #[test]
+    fn test_block_diff() {
+        let path = [PathIndex::Object("body".to_string()), PathIndex::Array(1)];
+        let block = json!("<p>a</p>");
+        let mut diff = BTreeMap::new();
+        block_diff(
+            Some(&block),
+            None,
+            "block.json",
+            &path,
+            &mut diff,
+            &mut 0,
+            &build_args(&["--sort-by", "size"]),
+        );
+        assert!(DIFF_SIZES.contains_key(&("block.json".to_string(), "body.1".to_string())));
+        let mut results = vec![("block.json".to_string(), diff)];
+        resolve_refs(&mut results, None);
+        let rendered = &results[0].1["body.1"];
+        assert!(rendered.starts_with("block only in A:"));
+        assert!(rendered.contains("&lt;p&gt;a&lt;/p&gt;"), "{rendered}");
+
+        let mut diff = BTreeMap::new();
+        let args = build_args(&["--summary-only"]);
+        block_diff(None, Some(&block), "f", &path, &mut diff, &mut 0, &args);
+        assert_eq!(diff["body.1"], "");
+
+        let mut diff = BTreeMap::new();
+        let args = build_args(&["--dts-only"]);
+        block_diff(
+            None,
+            Some(&json!({"a": 1})),
+            "f",
+            &path,
+            &mut diff,
+            &mut 0,
+            &args,
+        );
+        assert!(diff.is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::Error;
+use dashmap::DashMap;
+use rayon::prelude::*;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+
+/// Url → body of every `index.json` fetched so far, `None` if it doesn't exist.
+///
+/// Kept across runs with `--watch`, as the remote side doesn't change while watching.
+static FETCHED: LazyLock<DashMap<String, Option<String>>> = LazyLock::new(DashMap::new);
+
+/// The base url if `root` is an `http://` or `https://` url rather than a local path.
+pub fn remote_base(root: &Path) -> Option<&str> {
+    root.to_str()
+        .filter(|root| root.starts_with("http://") || root.starts_with("https://"))
+}
+
+/// Fetch `<base>/<key>` for every key in parallel, skipping keys that don't exist remotely.
+pub fn fetch_index_jsons(
+    base: &str,
+    keys: &HashSet<String>,
+) -> Result<Vec<(String, String)>, Error> {
+    let client = Client::new();
+    let base = base.trim_end_matches('/');
+    keys.par_iter()
+        .map(|key| {
+            let url = format!("{base}/{key}");
+            let body = match FETCHED.get(&url) {
+                Some(body) => body.clone(),
+                None => {
+                    let body = fetch(&client, &url)?;
+                    FETCHED.insert(url, body.clone());
+                    body
+                }
+            };
+            Ok(body.map(|body| (key.clone(), body)))
+        })
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+fn fetch(client: &Client, url: &str) -> Result<Option<String>, Error> {
+    let res = client.get(url).send()?;
+    if res.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(res.error_for_status()?.text()?))
+}
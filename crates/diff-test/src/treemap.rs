@@ -0,0 +1,110 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Error;
+use quick_xml::escape::escape;
+
+/// Nodes deeper than this are folded into their parent to keep the page small.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Default)]
+struct Node {
+    count: usize,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn add(&mut self, segments: &[&str], count: usize) {
+        self.count += count;
+        if let [segment, rest @ ..] = segments {
+            self.children
+                .entry(segment.to_string())
+                .or_default()
+                .add(rest, count);
+        }
+    }
+
+    fn write(&self, out: &mut impl Write, path: &str, depth: usize) -> Result<(), Error> {
+        let label = if path.is_empty() { "all" } else { path };
+        let label = escape(label);
+        write!(
+            out,
+            r#"<div class="n d{}" style="flex-grow: {}" title="{label}: {}"><span>{label} ({})</span>"#,
+            depth % 2,
+            self.count,
+            self.count,
+            self.count
+        )?;
+        if depth < MAX_DEPTH {
+            let mut children = self.children.iter().collect::<Vec<_>>();
+            // Stable sort, so equally dense subtrees stay in path order.
+            children.sort_by_key(|(_, child)| Reverse(child.count));
+            for (segment, child) in children {
+                let child_path = if path.is_empty() {
+                    segment.to_string()
+                } else {
+                    format!("{path}/{segment}")
+                };
+                child.write(out, &child_path, depth + 1)?;
+            }
+        }
+        write!(out, "</div>")?;
+        Ok(())
+    }
+}
+
+/// Write an html treemap of `(file, number of diffs)` along the `/`-separated file paths.
+///
+/// Each box is sized by the diffs below it, clicking a box zooms into it, clicking the zoomed
+/// box zooms out again.
+pub fn write_treemap(path: &Path, results: &[(String, usize)]) -> Result<(), Error> {
+    let mut root = Node::default();
+    for (file, count) in results.iter().filter(|(_, count)| *count > 0) {
+        let file = file.strip_suffix("/index.json").unwrap_or(file);
+        root.add(&file.split('/').collect::<Vec<_>>(), *count);
+    }
+    let file = File::create(path)?;
+    let mut out = BufWriter::new(file);
+    write!(
+        out,
+        r#"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8" />
+<title>Diff treemap</title>
+<style>
+body {{ margin: 0; height: 100vh; font: 12px sans-serif; }}
+.n {{ display: flex; flex-basis: 0; min-width: 0; min-height: 0; overflow: hidden; box-sizing: border-box; border: 1px solid #fff; background: rgb(0 0 0 / 6%); cursor: zoom-in; }}
+.d0 {{ flex-direction: column; }}
+.d1 {{ flex-direction: row; }}
+.n > span {{ flex: none; white-space: nowrap; padding: 1px 2px; }}
+.zoom {{ position: fixed; inset: 0; z-index: 1; background: #eee; cursor: zoom-out; }}
+body > .n {{ height: 100%; }}
+</style>
+</head>
+<body>
+"#
+    )?;
+    root.write(&mut out, "", 0)?;
+    write!(
+        out,
+        r#"
+<script>
+document.addEventListener("click", (e) => {{
+  const node = e.target.closest(".n");
+  if (!node) return;
+  const zoomed = document.querySelector(".zoom");
+  zoomed?.classList.remove("zoom");
+  if (node !== zoomed) node.classList.add("zoom");
+}});
+</script>
+</body>
+</html>
+"#
+    )?;
+    out.flush()?;
+    Ok(())
+}
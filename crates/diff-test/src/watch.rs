@@ -0,0 +1,58 @@
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use crate::walk_builder;
+
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Number of files and latest modification time across the watched roots.
+pub type Snapshot = (usize, Option<SystemTime>);
+
+/// Take a cheap snapshot of the roots' `index.json` files (or archive files).
+pub fn snapshot(roots: &[&Path], ignore_file: Option<&Path>) -> Snapshot {
+    roots.iter().fold((0, None), |(count, mtime), root| {
+        if root.is_file() {
+            let modified = root.metadata().and_then(|m| m.modified()).ok();
+            return (count + 1, mtime.max(modified));
+        }
+        let Ok(builder) = walk_builder(root, ignore_file) else {
+            return (count, mtime);
+        };
+        builder
+            .build()
+            .filter_map(Result::ok)
+            .filter(|f| f.file_type().map(|ft| ft.is_file()).unwrap_or(false))
+            .fold((count, mtime), |(count, mtime), f| {
+                let modified = f.metadata().ok().and_then(|m| m.modified().ok());
+                (count + 1, mtime.max(modified))
+            })
+    })
+}
+
+/// Block until the roots change and then stay unchanged for the debounce period.
+///
+/// Changes are found by polling: every `interval` both roots are walked again, which
+/// takes a while on a full build, so the interval shouldn't be too short.
+///
+/// Returns the settled snapshot, so a rebuild touching many files triggers a single re-run.
+pub fn wait_for_change(
+    roots: &[&Path],
+    ignore_file: Option<&Path>,
+    last: Snapshot,
+    interval: Duration,
+) -> Snapshot {
+    let mut current = last;
+    while current == last {
+        sleep(interval);
+        current = snapshot(roots, ignore_file);
+    }
+    loop {
+        sleep(DEBOUNCE);
+        let next = snapshot(roots, ignore_file);
+        if next == current {
+            return next;
+        }
+        current = next;
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::HashSet;
 use std::io::Cursor;
 
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use quick_xml::writer::Writer;
+use similar::{ChangeTag, TextDiff};
 
 pub fn fmt_html(html: &str) -> String {
     let mut reader = Reader::from_str(html);
@@ -20,3 +22,212 @@ pub fn fmt_html(html: &str) -> String {
     let result = writer.into_inner().into_inner();
     String::from_utf8(result).unwrap()
 }
+
+/// Elements that never have an end tag.
+const VOID_ELEMENTS: &[&[u8]] = &[
+    b"area", b"base", b"br", b"col", b"embed", b"hr", b"img", b"input", b"link", b"meta",
+    b"source", b"track", b"wbr",
+];
+
+/// Check that every non-void element in `html` is closed, in order.
+pub fn check_html(html: &str) -> Result<(), String> {
+    let mut reader = Reader::from_str(html);
+    reader.config_mut().check_end_names = false;
+    let mut open = vec![];
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = e.name().as_ref().to_ascii_lowercase();
+                if !VOID_ELEMENTS.contains(&name.as_slice()) {
+                    open.push(name);
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = e.name().as_ref().to_ascii_lowercase();
+                if VOID_ELEMENTS.contains(&name.as_slice()) {
+                    continue;
+                }
+                let name = String::from_utf8_lossy(&name);
+                match open.pop() {
+                    Some(expected) if expected == name.as_bytes() => {}
+                    Some(expected) => {
+                        return Err(format!(
+                            "expected </{}> but found </{name}> at {}",
+                            String::from_utf8_lossy(&expected),
+                            reader.buffer_position()
+                        ))
+                    }
+                    None => {
+                        return Err(format!(
+                            "unexpected </{name}> at {}",
+                            reader.buffer_position()
+                        ))
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("{e} at {}", reader.error_position())),
+        }
+    }
+    match open.pop() {
+        Some(name) => Err(format!("unclosed <{}>", String::from_utf8_lossy(&name))),
+        None => Ok(()),
+    }
+}
+
+/// Names of the elements touched by the changes between two [`fmt_html`] outputs.
+///
+/// A changed start tag counts for its element, changed text for the element containing it.
+/// End tags are not counted, as they only change along with their start tag or content.
+pub fn changed_elements(lhs: &str, rhs: &str) -> Vec<String> {
+    let diff = TextDiff::from_lines(lhs, rhs);
+    let mut deleted = HashSet::new();
+    let mut inserted = HashSet::new();
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Delete => deleted.extend(change.old_index()),
+            ChangeTag::Insert => inserted.extend(change.new_index()),
+            ChangeTag::Equal => {}
+        }
+    }
+    let mut elements = vec![];
+    elements_at_lines(lhs, &deleted, &mut elements);
+    elements_at_lines(rhs, &inserted, &mut elements);
+    elements
+}
+
+fn elements_at_lines(html: &str, lines: &HashSet<usize>, elements: &mut Vec<String>) {
+    let tag_name = |tag: &str| {
+        tag.split(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    let mut open: Vec<String> = vec![];
+    for (i, line) in html.lines().enumerate() {
+        // A line can hold several tags and text, like `<li>one</li>` or `</code>two</li>`.
+        // It counts for its first start tag, or for the element containing its first text.
+        let mut element = None;
+        let (text, tags) = line.trim().split_once('<').unwrap_or((line.trim(), ""));
+        if !text.is_empty() {
+            element = open.last().cloned();
+        }
+        for piece in tags.split('<').filter(|_| !tags.is_empty()) {
+            let (tag, text) = piece.split_once('>').unwrap_or((piece, ""));
+            if tag.starts_with('/') {
+                open.pop();
+            } else if !tag.starts_with(['!', '?']) {
+                let name = tag_name(tag);
+                if !tag.ends_with('/') && !VOID_ELEMENTS.contains(&name.as_bytes()) {
+                    open.push(name.clone());
+                }
+                element.get_or_insert(name);
+            }
+            if !text.is_empty() && element.is_none() {
+                element = open.last().cloned();
+            }
+        }
+        if let Some(element) = element.filter(|_| lines.contains(&i)) {
+            elements.push(element);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_html_well_formed() {
+        assert_eq!(check_html("<div><p>a <em>b</em></p><p>c</p></div>"), Ok(()));
+        assert_eq!(check_html("<DIV><p>a</p></div>"), Ok(()));
+        assert_eq!(check_html(""), Ok(()));
+    }
+
+    #[test]
+    fn test_check_html_mismatched_close() {
+        let err = check_html("<div><p>a</div></p>").unwrap_err();
+        assert!(err.starts_with("expected </p> but found </div>"), "{err}");
+    }
+
+    #[test]
+    fn test_check_html_unclosed() {
+        assert_eq!(
+            check_html("<div><p>a</p>"),
+            Err("unclosed <div>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_html_void_elements() {
+        assert_eq!(check_html("<p>a<br>b<img src=\"x.png\"></p>"), Ok(()));
+        assert_eq!(check_html("<p>a<br/>b<hr /></p>"), Ok(()));
+        // A stray end tag of a void element is ignored, like browsers do.
+        assert_eq!(check_html("<p>a<br></br>b</p>"), Ok(()));
+    }
+
+    #[test]
+    fn test_check_html_stray_end_tag() {
+        // quick-xml reports this one itself.
+        let err = check_html("<p>a</p></div>").unwrap_err();
+        assert!(err.contains("`</div>`") && err.ends_with(" at 8"), "{err}");
+    }
+
+    fn changed(lhs: &str, rhs: &str) -> Vec<String> {
+        changed_elements(&fmt_html(lhs), &fmt_html(rhs))
+    }
+
+    #[test]
+    fn test_changed_elements_attribute() {
+        assert_eq!(
+            changed(
+                r#"<div class="a"><p>x</p></div>"#,
+                r#"<div class="b"><p>x</p></div>"#
+            ),
+            ["div", "div"]
+        );
+    }
+
+    #[test]
+    fn test_changed_elements_nested_text() {
+        let html = |text| {
+            format!("<section><ul><li>one</li><li><code>two</code> {text}</li></ul>after</section>")
+        };
+        // A line counts for its first start tag, even if the change is in text after it.
+        assert_eq!(changed(&html("more"), &html("less")), ["code", "code"]);
+        assert_eq!(
+            changed(
+                "<section><ul><li>one</li></ul>after</section>",
+                "<section><ul><li>one</li></ul>later</section>"
+            ),
+            ["section", "section"]
+        );
+        assert_eq!(
+            changed(
+                "<section><ul><li>one</li><li>two</li></ul></section>",
+                "<section><ul><li>one</li><li>three</li></ul></section>"
+            ),
+            ["li", "li"]
+        );
+    }
+
+    #[test]
+    fn test_changed_elements_void() {
+        assert_eq!(
+            changed(
+                r#"<div><img src="a.png"/><p>x</p></div>"#,
+                r#"<div><img src="b.png"/><p>x</p></div>"#
+            ),
+            ["img", "img"]
+        );
+        // Void elements don't stay open, with or without a closing slash.
+        for hr in ["<hr>", "<hr/>"] {
+            let html = |text| format!("<section>\n<div>\n{hr}\n</div>{text}\n</section>");
+            assert_eq!(
+                changed_elements(&html("after"), &html("later")),
+                ["section", "section"]
+            );
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use std::env;
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::mpsc::channel;
 use std::sync::Arc;
@@ -20,6 +20,7 @@ use rari_doc::build::{
     build_generic_pages, build_spas, build_top_level_meta,
 };
 use rari_doc::cached_readers::{read_and_cache_doc_pages, CACHED_DOC_PAGE_FILES};
+use rari_doc::html::links::LINK_RECORDER_SENDER;
 use rari_doc::issues::IN_MEMORY;
 use rari_doc::pages::json::BuiltPage;
 use rari_doc::pages::page::Page;
@@ -163,8 +164,52 @@ struct UpdateArgs {
 
 #[derive(Args)]
 struct ServeArgs {
-    #[arg(long, help = "Caution! Don't use when editing content.")]
+    #[arg(
+        long,
+        help = "Caution! Don't use when editing content. Requests with ?nocache=1 or Cache-Control: no-cache still bypass the cache."
+    )]
     cache: bool,
+    #[arg(long, help = "Content root to serve for requests with ?root=a")]
+    root_a: Option<PathBuf>,
+    #[arg(long, help = "Content root to serve for requests with ?root=b")]
+    root_b: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Don't send CSP and other security headers (for debugging)"
+    )]
+    no_security_headers: bool,
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "/en-US/docs/Web",
+        value_name = "URL_PATH",
+        help = "Open <URL_PATH> in the browser once the server is listening"
+    )]
+    open: Option<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Answer with 503 when more than <N> requests are in flight"
+    )]
+    max_concurrency: Option<usize>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Append a Common Log Format line per request to <PATH>"
+    )]
+    access_log: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "USER:PASS",
+        help = "Require HTTP Basic credentials <USER:PASS> for every request"
+    )]
+    basic_auth: Option<String>,
+    #[arg(
+        long,
+        value_name = "REQS_PER_SEC",
+        help = "Answer with 429 when a client sends more than <REQS_PER_SEC> requests per second"
+    )]
+    rate_limit: Option<u32>,
 }
 
 #[derive(Args)]
@@ -202,6 +247,11 @@ struct BuildArgs {
     sitemaps: bool,
     #[arg(long, help = "Display template statistics (debugging")]
     templ_stats: bool,
+    #[arg(
+        long,
+        help = "Write every link rendered by macros to <LINKS> as JSON lines"
+    )]
+    links: Option<PathBuf>,
     #[arg(long, help = "Write all issues to path <ISSUES>")]
     issues: Option<PathBuf>,
     #[arg(long, help = "Annotate html with 'data-flaw' attributes")]
@@ -336,6 +386,25 @@ fn main() -> Result<(), Error> {
                 None
             };
 
+            let links = if let Some(links_path) = args.links {
+                let (tx, rx) = channel();
+                LINK_RECORDER_SENDER
+                    .set(tx.clone())
+                    .expect("unable to create link recorder");
+                let mut buffed = BufWriter::new(File::create(links_path)?);
+                let recorder_handler = spawn(move || -> Result<(), Error> {
+                    while let Ok(Some(record)) = rx.recv() {
+                        serde_json::to_writer(&mut buffed, &record)?;
+                        buffed.write_all(b"\n")?;
+                    }
+                    buffed.flush()?;
+                    Ok(())
+                });
+                Some((recorder_handler, tx))
+            } else {
+                None
+            };
+
             let cache = match (arg_files.is_empty(), args.no_cache) {
                 (_, true) => Cache::None,
                 (true, false) => Cache::Static,
@@ -447,6 +516,13 @@ fn main() -> Result<(), Error> {
                     .expect("unable to close templ recorder");
             }
 
+            if let Some((recorder_handler, tx)) = links {
+                tx.send(None)?;
+                recorder_handler
+                    .join()
+                    .expect("unable to close link recorder")?;
+            }
+
             if let Some(issues_path) = args.issues {
                 let events = memory_layer.get_events();
                 let file = File::create(issues_path).unwrap();
@@ -460,7 +536,22 @@ fn main() -> Result<(), Error> {
             settings.data_issues = true;
             settings.blog_unpublished = true;
             let _ = SETTINGS.set(settings);
-            serve::serve()?
+            let mut content_roots = HashMap::new();
+            for (name, root) in [("a", args.root_a), ("b", args.root_b)] {
+                if let Some(root) = root {
+                    let root: &'static Path = Box::leak(root.canonicalize()?.into_boxed_path());
+                    content_roots.insert(name.to_string(), root);
+                }
+            }
+            serve::serve(serve::ServeOptions {
+                content_roots,
+                security_headers: !args.no_security_headers,
+                open: args.open,
+                max_concurrency: args.max_concurrency,
+                access_log: args.access_log,
+                basic_auth: args.basic_auth,
+                rate_limit: args.rate_limit,
+            })?
         }
         Commands::GitHistory => {
             info!("Gathering history 📜");
@@ -1,52 +1,346 @@
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
 
-use axum::body::Body;
-use axum::extract::{Path, Request};
-use axum::http::{header, StatusCode};
+use anyhow::Context;
+use axum::body::{Body, HttpBody};
+use axum::extract::{ConnectInfo, Path, Query, Request, State};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use axum::middleware::{from_fn, map_response, Next};
 use axum::response::{IntoResponse, Response};
 use axum::routing::get;
 use axum::{Json, Router};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use dashmap::DashMap;
 use rari_doc::cached_readers::wiki_histories;
 use rari_doc::contributors::contributors_txt;
 use rari_doc::error::DocError;
-use rari_doc::issues::{to_display_issues, IN_MEMORY};
+use rari_doc::issues::{to_display_issues, DisplayIssues, IN_MEMORY};
 use rari_doc::pages::json::BuiltPage;
 use rari_doc::pages::page::{Page, PageBuilder, PageLike};
 use rari_doc::pages::types::doc::Doc;
 use rari_doc::reader::read_docs_parallel;
-use rari_types::globals::{self, content_root, content_translated_root};
+use rari_doc::resolve::{parse_url, url_meta_and_canonical_url_from};
+use rari_types::error::EnvError;
+use rari_types::globals::{
+    self, content_root, content_translated_root, override_cache_content, override_content_root,
+    refresh_cache_content,
+};
 use rari_types::locale::Locale;
 use rari_types::Popularities;
+use rari_utils::concat_strs;
 use rari_utils::io::read_to_string;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::{error, span, Level};
 
 static REQ_COUNTER: AtomicU64 = AtomicU64::new(1);
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
 
-#[derive(Debug, Serialize)]
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Number of flaws of a served doc, so `curl -I` shows problem pages.
+const X_RARI_FLAWS: HeaderName = HeaderName::from_static("x-rari-flaws");
+
+const TEXT_PLAIN: HeaderValue = HeaderValue::from_static("text/plain; charset=utf-8");
+
+/// Headers added to every response but `/_health` unless disabled, mirroring production.
+const SECURITY_HEADERS: &[(HeaderName, &str)] = &[
+    (header::CONTENT_SECURITY_POLICY, "default-src 'self'"),
+    (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+    (header::REFERRER_POLICY, "strict-origin-when-cross-origin"),
+];
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 struct SearchItem {
     title: String,
     url: String,
 }
 
-async fn handler(req: Request) -> Response<Body> {
-    if req.uri().path().ends_with("/contributors.txt") {
-        get_contributors_handler(req).await.into_response()
-    } else {
-        get_json_handler(req).await.into_response()
+/// Options for the dev server.
+#[derive(Debug, Default)]
+pub(crate) struct ServeOptions {
+    /// Alternative content roots selectable per request via `?root=<name>`.
+    pub content_roots: HashMap<String, &'static std::path::Path>,
+    /// Add [`SECURITY_HEADERS`] to every response.
+    pub security_headers: bool,
+    /// Url path to open in the default browser once the server is listening.
+    pub open: Option<String>,
+    /// Answer with 503 instead of handling more than this many requests at once.
+    pub max_concurrency: Option<usize>,
+    /// File to append a Common Log Format line per request to.
+    pub access_log: Option<PathBuf>,
+    /// `user:pass` credentials required via HTTP Basic auth.
+    pub basic_auth: Option<String>,
+    /// Answer with 429 when a client sends more than this many requests per second.
+    pub rate_limit: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RootQuery {
+    root: Option<String>,
+    /// `?nocache=1` builds the page without cached content, see [`bypass_cache`].
+    nocache: Option<String>,
+}
+
+/// Whether to read everything fresh for this request, via `?nocache=1` or a
+/// `Cache-Control: no-cache` header (as sent by browsers on a hard reload).
+///
+/// Only matters when serving with `--cache`, without it nothing is cached anyway.
+/// Cached sidebars are re-read and replaced, so later requests get the fresh content too.
+/// Content that is cached once for the whole run (blog, curriculum, generic pages) is
+/// read fresh for this request only.
+fn bypass_cache(nocache: Option<&str>, headers: &HeaderMap) -> bool {
+    nocache.is_some_and(|nocache| !matches!(nocache, "0" | "false"))
+        || headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|directive| directive.trim().eq_ignore_ascii_case("no-cache"))
+}
+
+async fn handler(State(options): State<Arc<ServeOptions>>, req: Request) -> Response<Body> {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        let mut res = error_response(
+            ServeError::MethodNotAllowed,
+            format!("method not allowed: {}", req.method()),
+        );
+        res.headers_mut()
+            .insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+        return res;
+    }
+    if let Some(location) = normalized_location(req.uri().path()) {
+        let location = match req.uri().query() {
+            Some(query) => format!("{location}?{query}"),
+            None => location,
+        };
+        return (
+            StatusCode::MOVED_PERMANENTLY,
+            [(header::LOCATION, location)],
+        )
+            .into_response();
+    }
+    let query = Query::<RootQuery>::try_from_uri(req.uri())
+        .map(|Query(query)| query)
+        .unwrap_or_default();
+    let root = match &query.root {
+        Some(name) => match options.content_roots.get(name) {
+            Some(root) => Some(*root),
+            None => {
+                return error_response(
+                    ServeError::InvalidUrl,
+                    format!("unknown content root: {name}"),
+                )
+            }
+        },
+        None => None,
+    };
+    let refresh = bypass_cache(query.nocache.as_deref(), req.headers());
+    let html = prefers_html(req.headers());
+    let url = req.uri().path().to_string();
+    blocking(move || {
+        // The overrides are per thread, so they are set on the blocking thread doing
+        // the work. The content caches aren't keyed by content root, so `?root=`
+        // requests never read or fill them.
+        let _root = root.map(override_content_root);
+        let _cache = root.is_some().then(|| override_cache_content(false));
+        let _refresh = refresh.then(refresh_cache_content);
+        let res = if split_output_file(req.uri().path()).1 == Some("contributors.txt") {
+            Ok(get_contributors_handler(req))
+        } else {
+            get_json_handler(req)
+        };
+        // Still on the blocking thread, so suggestions come from the requested root.
+        match res {
+            Err(e) if html => html_error_response(&url, e),
+            res => res.into_response(),
+        }
+    })
+    .await
+    .unwrap_or_else(IntoResponse::into_response)
+}
+
+/// Run the blocking `f` on the blocking thread pool, so a slow build doesn't hold up
+/// the runtime and concurrent requests are actually in flight at the same time.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, AppError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Panic(e.to_string()))
+}
+
+/// Whether the `Accept` header lists `text/html` before any JSON type, like browsers do.
+fn prefers_html(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+    else {
+        return false;
+    };
+    accept
+        .split(',')
+        .map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+        .find(|media_type| *media_type == "text/html" || media_type.ends_with("json"))
+        == Some("text/html")
+}
+
+/// Levenshtein distance between `a` and `b`, by chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diag
+            } else {
+                1 + diag.min(above).min(row[j])
+            };
+            diag = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// The known doc url closest to `url`, if it is close enough to be a typo.
+///
+/// Reads the search index, so this blocks.
+fn suggest_url(url: &str) -> Option<String> {
+    // Unknown urls usually don't parse, so the suffix is stripped by hand.
+    let url = url.strip_suffix("/index.json").unwrap_or(url);
+    let locale = url
+        .split('/')
+        .nth(1)
+        .and_then(|locale| Locale::from_str(locale).ok())
+        .unwrap_or_default();
+    if locale != Locale::EnUs && content_translated_root().is_none() {
+        // Nothing to suggest without translated content.
+        return None;
     }
+    let index = search_index(locale)
+        .inspect_err(|e| tracing::warn!("unable to list urls for suggestions: {e}"))
+        .ok()?;
+    closest_url(url, index.iter().map(|item| item.url.as_str())).map(ToString::to_string)
+}
+
+/// The url of `known` closest to `url`, ignoring case, within a quarter of its length
+/// (but at least 2) edits.
+fn closest_url<'a>(url: &str, known: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let url = url.to_lowercase();
+    let max_distance = (url.len() / 4).max(2);
+    known
+        .map(|known| (edit_distance(&url, &known.to_lowercase()), known))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
 }
 
-async fn get_json_handler(req: Request) -> Result<Json<BuiltPage>, AppError> {
+/// A minimal html page for errors of `url`, for browsers instead of a JSON error.
+fn html_error_response(url: &str, err: AppError) -> Response<Body> {
+    let error_code = err.error_code();
+    if error_code == ServeError::BuildFailed {
+        error!("🤷: {}", err.message());
+    }
+    let status = error_code.status_code();
+    let suggestion = match error_code {
+        ServeError::NotFound | ServeError::InvalidUrl => suggest_url(url),
+        _ => None,
+    }
+    .map(|suggestion| {
+        let suggestion = html_escape::encode_double_quoted_attribute(&suggestion);
+        format!(r#"<p>Did you mean <a href="{suggestion}">{suggestion}</a>?</p>"#)
+    })
+    .unwrap_or_default();
+    let body = format!(
+        r#"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8" />
+<title>{status}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 50rem; margin: 3rem auto; }}
+code {{ background: #eee; padding: .2rem; }}
+pre {{ white-space: pre-wrap; color: #900; }}
+</style>
+</head>
+<body>
+<h1>{reason}</h1>
+<p><code>{url}</code></p>
+<pre>{message}</pre>
+{suggestion}
+</body>
+</html>
+"#,
+        reason = status.canonical_reason().unwrap_or_default(),
+        url = html_escape::encode_text(url),
+        message = html_escape::encode_text(&err.message()),
+    );
+    (
+        status,
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            // Takes precedence over the security headers' CSP, which would block the inline style.
+            (
+                header::CONTENT_SECURITY_POLICY,
+                "default-src 'none'; style-src 'unsafe-inline'",
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// `path` without the output file it points into, and that file, see [`rari_doc::resolve::ParsedUrl::suffix`].
+fn split_output_file(path: &str) -> (&str, Option<&'static str>) {
+    let suffix = parse_url(path).ok().and_then(|parsed| parsed.suffix);
+    let url = suffix
+        .and_then(|file| path.strip_suffix(file))
+        .and_then(|url| url.strip_suffix('/'))
+        .unwrap_or(path);
+    (url, suffix)
+}
+
+/// The canonical location for `path` if it only differs in the casing of the locale or
+/// in a trailing slash, like `/en-us/docs/Web/HTML/` or `/en-US/blog`.
+fn normalized_location(path: &str) -> Option<String> {
+    let (url, suffix) = split_output_file(path);
+    let suffix = suffix
+        .map(|file| concat_strs!("/", file))
+        .unwrap_or_default();
+    let trimmed = url.trim_end_matches('/');
+    let (_, canonical) = url_meta_and_canonical_url_from(trimmed).ok()?;
+    let location = if suffix.is_empty() {
+        canonical
+    } else {
+        concat_strs!(canonical.trim_end_matches('/'), &suffix)
+    };
+    let location_path = location.strip_suffix(suffix.as_str()).unwrap_or(&location);
+    (location != path
+        && location_path
+            .trim_end_matches('/')
+            .eq_ignore_ascii_case(trimmed))
+    .then_some(location)
+}
+
+fn get_json_handler(req: Request) -> Result<Response, AppError> {
     let url = req.uri().path();
     let req_id = REQ_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     let span = span!(Level::WARN, "serve", req = req_id);
     let _enter0 = span.enter();
     let span = span!(Level::ERROR, "url", "{}", url);
     let _enter1 = span.enter();
-    let url = url.strip_suffix("/index.json").unwrap_or(url);
+    let (url, _) = split_output_file(url);
     let page = Page::from_url_with_fallback(url)?;
     let file = page.full_path().to_string_lossy();
     let span = span!(
@@ -57,24 +351,80 @@ async fn get_json_handler(req: Request) -> Result<Json<BuiltPage>, AppError> {
         file = file.as_ref()
     );
     let _enter2 = span.enter();
-    let mut json = page.build()?;
+    let mut json = build_page(&page)?;
     tracing::info!("{url}");
+    let mut flaws = None;
     if let BuiltPage::Doc(json_doc) = &mut json {
         let m = IN_MEMORY.get_events();
         let (_, req_issues) = m
             .remove(page.full_path().to_string_lossy().as_ref())
             .unwrap_or_default();
+        flaws = Some(req_issues.len());
         json_doc.doc.flaws = Some(to_display_issues(req_issues, &page));
     }
-    Ok(Json(json))
+    let mut res = Json(json).into_response();
+    if let Some(flaws) = flaws {
+        res.headers_mut()
+            .insert(X_RARI_FLAWS, HeaderValue::from(flaws));
+    }
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildQuery {
+    url: String,
+}
+
+/// How building a page went, without the built content.
+#[derive(Debug, Serialize)]
+struct BuildInfo {
+    build_ms: u64,
+    /// Issues recorded while building, grouped like the `flaws` of docs.
+    flaws: DisplayIssues,
+}
+
+async fn get_build_handler(
+    Query(BuildQuery { url }): Query<BuildQuery>,
+) -> Result<Json<BuildInfo>, AppError> {
+    blocking(move || build_info(&url)).await?.map(Json)
+}
+
+fn build_info(url: &str) -> Result<BuildInfo, AppError> {
+    let req_id = REQ_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let span = span!(Level::WARN, "serve", req = req_id);
+    let _enter0 = span.enter();
+    let span = span!(Level::ERROR, "url", "{}", url);
+    let _enter1 = span.enter();
+    let (url, _) = split_output_file(url);
+    let page = Page::from_url_with_fallback(url)?;
+    let file = page.full_path().to_string_lossy();
+    let span = span!(
+        Level::ERROR,
+        "page",
+        locale = page.locale().as_url_str(),
+        slug = page.slug(),
+        file = file.as_ref()
+    );
+    let _enter2 = span.enter();
+    let start = Instant::now();
+    build_page(&page)?;
+    let build_ms = start.elapsed().as_millis() as u64;
+    let (_, issues) = IN_MEMORY
+        .get_events()
+        .remove(file.as_ref())
+        .unwrap_or_default();
+    Ok(BuildInfo {
+        build_ms,
+        flaws: to_display_issues(issues, &page),
+    })
 }
 
-async fn get_contributors_handler(req: Request) -> impl IntoResponse {
+fn get_contributors_handler(req: Request) -> Response {
     let url = req.uri().path();
-    match get_contributors(url.strip_suffix("/contributors.txt").unwrap_or(url)) {
+    match get_contributors(split_output_file(url).0) {
         Ok(contributors_txt_str) => (
             StatusCode::OK,
-            [(header::CONTENT_TYPE, "text/plain")],
+            [(header::CONTENT_TYPE, TEXT_PLAIN)],
             contributors_txt_str,
         )
             .into_response(),
@@ -87,7 +437,7 @@ async fn get_contributors_handler(req: Request) -> impl IntoResponse {
 
 fn get_contributors(url: &str) -> Result<String, AppError> {
     let page = Page::from_url_with_fallback(url)?;
-    let json = page.build()?;
+    let json = build_page(&page)?;
     let github_file_url = if let BuiltPage::Doc(ref doc) = json {
         &doc.doc.source.github_url
     } else {
@@ -101,12 +451,44 @@ fn get_contributors(url: &str) -> Result<String, AppError> {
     Ok(contributors_txt_str)
 }
 
+#[derive(Debug, Default, Deserialize)]
+struct NoCacheQuery {
+    nocache: Option<String>,
+}
+
 async fn get_search_index_handler(
     Path(locale): Path<String>,
+    Query(query): Query<NoCacheQuery>,
+    headers: HeaderMap,
 ) -> Result<Json<Vec<SearchItem>>, AppError> {
     tracing::info!("search index for: {locale}");
     let locale = Locale::from_str(&locale)?;
-    Ok(Json(get_search_index(locale)?))
+    let refresh = bypass_cache(query.nocache.as_deref(), &headers);
+    let index = blocking(move || {
+        let _refresh = refresh.then(refresh_cache_content);
+        search_index(locale)
+    })
+    .await??;
+    Ok(Json(index.to_vec()))
+}
+
+type SearchIndexKey = (&'static std::path::Path, Locale);
+
+/// Search indexes per content root and locale, built on first use.
+static SEARCH_INDEXES: LazyLock<Mutex<HashMap<SearchIndexKey, Arc<Vec<SearchItem>>>>> =
+    LazyLock::new(Default::default);
+
+/// The cached search index of `locale`, rebuilt when refreshing the cache.
+fn search_index(locale: Locale) -> Result<Arc<Vec<SearchItem>>, DocError> {
+    let key = (content_root(), locale);
+    if !globals::refresh_cache() {
+        if let Some(index) = SEARCH_INDEXES.lock().unwrap().get(&key) {
+            return Ok(index.clone());
+        }
+    }
+    let index = Arc::new(get_search_index(locale)?);
+    SEARCH_INDEXES.lock().unwrap().insert(key, index.clone());
+    Ok(index)
 }
 
 fn get_search_index(locale: Locale) -> Result<Vec<SearchItem>, DocError> {
@@ -119,7 +501,7 @@ fn get_search_index(locale: Locale) -> Result<Vec<SearchItem>, DocError> {
         &[&if locale == Locale::EnUs {
             content_root()
         } else {
-            content_translated_root().expect("no TRANSLATED_CONTENT_ROOT set")
+            content_translated_root().ok_or(EnvError::NoTranslatedContent)?
         }
         .join(locale.as_folder_str())],
         None,
@@ -153,18 +535,409 @@ fn get_search_index(locale: Locale) -> Result<Vec<SearchItem>, DocError> {
     Ok(out)
 }
 
+#[derive(Debug, Serialize)]
+struct ServerIndex {
+    name: &'static str,
+    version: &'static str,
+    endpoints: &'static [&'static str],
+    examples: &'static [&'static str],
+}
+
+async fn get_index_handler() -> Json<ServerIndex> {
+    Json(ServerIndex {
+        name: "rari",
+        version: env!("CARGO_PKG_VERSION"),
+        endpoints: &[
+            "/{locale}/docs/{slug}/index.json",
+            "/{locale}/docs/{slug}/contributors.txt",
+            "/{locale}/search-index.json",
+            "/_search?q={query}&locale={locale}&limit={limit}",
+            "/_build?url={url}",
+            "/_health",
+        ],
+        examples: &[
+            "/en-US/docs/Web/HTML/index.json",
+            "/en-US/docs/Web/HTML/contributors.txt",
+            "/en-US/search-index.json",
+            "/_search?q=grid",
+            "/_build?url=/en-US/docs/Web/HTML",
+        ],
+    })
+}
+
+/// Liveness check, without basic auth or rate limiting.
+async fn get_health_handler() -> &'static str {
+    "ok"
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: String,
+    locale: Option<String>,
+    limit: Option<usize>,
+    nocache: Option<String>,
+}
+
+async fn get_search_handler(
+    Query(query): Query<SearchQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SearchItem>>, AppError> {
+    tracing::info!("search for: {}", query.q);
+    let locale = query
+        .locale
+        .as_deref()
+        .map(Locale::from_str)
+        .transpose()?
+        .unwrap_or_default();
+    let refresh = bypass_cache(query.nocache.as_deref(), &headers);
+    let hits = blocking(move || {
+        let _refresh = refresh.then(refresh_cache_content);
+        let index = search_index(locale)?;
+        Ok::<_, DocError>(search(
+            &index,
+            &query.q,
+            query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        ))
+    })
+    .await??;
+    Ok(Json(hits))
+}
+
+/// Case-insensitive substring search over page titles and urls of `index`.
+///
+/// Exact title matches rank first, then title prefixes, title substrings and
+/// finally url matches. Within a rank the popularity order of the search index
+/// is kept.
+fn search(index: &[SearchItem], q: &str, limit: usize) -> Vec<SearchItem> {
+    let q = q.to_lowercase();
+    let mut hits = index
+        .iter()
+        .filter_map(|item| {
+            let title = item.title.to_lowercase();
+            let rank = if title == q {
+                0
+            } else if title.starts_with(&q) {
+                1
+            } else if title.contains(&q) {
+                2
+            } else if item.url.to_lowercase().contains(&q) {
+                3
+            } else {
+                return None;
+            };
+            Some((rank, item))
+        })
+        .collect::<Vec<_>>();
+    hits.sort_by_key(|(rank, _)| *rank);
+    hits.into_iter()
+        .take(limit)
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+async fn add_security_headers(mut res: Response) -> Response {
+    let headers = res.headers_mut();
+    for (name, value) in SECURITY_HEADERS {
+        headers
+            .entry(name.clone())
+            .or_insert(HeaderValue::from_static(value));
+    }
+    res
+}
+
+/// A request counted in [`IN_FLIGHT`] until dropped.
+struct InFlight;
+
+impl InFlight {
+    fn acquire(max: usize) -> Option<Self> {
+        IN_FLIGHT
+            .fetch_update(
+                std::sync::atomic::Ordering::AcqRel,
+                std::sync::atomic::Ordering::Acquire,
+                |n| (n < max).then_some(n + 1),
+            )
+            .ok()
+            .map(|_| InFlight)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, std::sync::atomic::Ordering::AcqRel);
+    }
+}
+
+async fn limit_concurrency(max: usize, req: Request, next: Next) -> Response {
+    let Some(_in_flight) = InFlight::acquire(max) else {
+        let mut res = error_response(
+            ServeError::Unavailable,
+            format!("more than {max} requests in flight"),
+        );
+        res.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+        return res;
+    };
+    next.run(req).await
+}
+
+/// Paths that skip basic auth and rate limiting, so liveness probes always get through.
+const UNRESTRICTED_PATHS: &[&str] = &["/_health"];
+
+/// Forget full buckets once this many clients are tracked.
+const MAX_BUCKETS: usize = 1024;
+
+/// Look for full buckets to forget at most this often.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A token bucket per client ip, holding up to one second worth of requests.
+///
+/// Buckets are refilled lazily when the client sends its next request.
+struct RateLimiter {
+    rate: f64,
+    buckets: DashMap<IpAddr, Bucket>,
+    /// When full buckets were last forgotten.
+    swept: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(rate: u32) -> Self {
+        Self {
+            rate: f64::from(rate.max(1)),
+            buckets: DashMap::new(),
+            swept: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Take a token for `ip`, or return the seconds until the next token is available.
+    fn acquire(&self, ip: IpAddr, now: Instant) -> Result<(), u64> {
+        if self.buckets.len() > MAX_BUCKETS {
+            self.sweep(now);
+        }
+        let mut bucket = self.buckets.entry(ip).or_insert(Bucket {
+            tokens: self.rate,
+            updated: now,
+        });
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * self.rate;
+        bucket.tokens = (bucket.tokens + refill).min(self.rate);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / self.rate).ceil() as u64)
+        }
+    }
+
+    /// Forget the buckets that are full again, unless that was done within the last
+    /// [`SWEEP_INTERVAL`] or another request is doing it right now.
+    fn sweep(&self, now: Instant) {
+        let Ok(mut swept) = self.swept.try_lock() else {
+            return;
+        };
+        if now.saturating_duration_since(*swept) < SWEEP_INTERVAL {
+            return;
+        }
+        *swept = now;
+        self.buckets.retain(|_, bucket| {
+            bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * self.rate < self.rate
+        });
+    }
+}
+
+async fn rate_limit(limiter: Arc<RateLimiter>, req: Request, next: Next) -> Response {
+    if UNRESTRICTED_PATHS.contains(&req.uri().path()) {
+        return next.run(req).await;
+    }
+    let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>() else {
+        return next.run(req).await;
+    };
+    if let Err(retry_after) = limiter.acquire(addr.ip(), Instant::now()) {
+        let mut res = error_response(
+            ServeError::TooManyRequests,
+            format!("more than {} requests per second", limiter.rate),
+        );
+        res.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+        return res;
+    }
+    next.run(req).await
+}
+
+/// The `user:pass` credentials of a `Basic` authorization header, see RFC 7617.
+fn basic_credentials(value: &HeaderValue) -> Option<Vec<u8>> {
+    let (scheme, token) = value.to_str().ok()?.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    BASE64_STANDARD.decode(token.trim()).ok()
+}
+
+/// Compare `a` and `b` in time depending only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+async fn require_basic_auth(expected: Arc<str>, req: Request, next: Next) -> Response {
+    if UNRESTRICTED_PATHS.contains(&req.uri().path())
+        || req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(basic_credentials)
+            .is_some_and(|credentials| constant_time_eq(&credentials, expected.as_bytes()))
+    {
+        return next.run(req).await;
+    }
+    let mut res = error_response(
+        ServeError::Unauthorized,
+        "missing or wrong credentials".to_string(),
+    );
+    res.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        HeaderValue::from_static(r#"Basic realm="rari", charset="UTF-8""#),
+    );
+    res
+}
+
+/// Append a line in NCSA Common Log Format for the request to `log`.
+async fn access_log(log: Arc<Mutex<File>>, req: Request, next: Next) -> Response {
+    let remote = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let time = chrono::Local::now().format("%d/%b/%Y:%H:%M:%S %z");
+    let request_line = format!("{} {} {:?}", req.method(), req.uri(), req.version());
+    let res = next.run(req).await;
+    let bytes = res
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| res.body().size_hint().exact().map(|len| len.to_string()))
+        .unwrap_or_else(|| "-".to_string());
+    let line = format!(
+        "{remote} - - [{time}] \"{request_line}\" {} {bytes}\n",
+        res.status().as_u16()
+    );
+    if let Err(e) = log.lock().unwrap().write_all(line.as_bytes()) {
+        tracing::warn!("unable to write access log: {e}");
+    }
+    res
+}
+
+/// Build `page`, see [`catch_panic`].
+fn build_page(page: &Page) -> Result<BuiltPage, AppError> {
+    catch_panic(|| page.build())
+}
+
+/// Run `f`, turning a panic (e.g. an unwrap deep in a macro) into an error, so the
+/// request gets a 500 with the panic message instead of a dropped connection.
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, DocError>) -> Result<T, AppError> {
+    match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(res) => Ok(res?),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            Err(AppError::Panic(message.to_string()))
+        }
+    }
+}
+
 #[derive(Debug)]
-struct AppError(DocError);
+enum AppError {
+    Doc(DocError),
+    /// Building the page panicked with this message.
+    Panic(String),
+}
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response<Body> {
-        match self.0 {
+impl AppError {
+    fn error_code(&self) -> ServeError {
+        match self {
+            AppError::Doc(err) => ServeError::from(err),
+            AppError::Panic(_) => ServeError::BuildFailed,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            AppError::Doc(err) => err.to_string(),
+            AppError::Panic(message) => concat_strs!("panicked while building: ", message),
+        }
+    }
+}
+
+/// Stable error codes returned to clients, independent of the error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ServeError {
+    NotFound,
+    InvalidUrl,
+    Unauthorized,
+    MethodNotAllowed,
+    Unavailable,
+    TooManyRequests,
+    BuildFailed,
+}
+
+impl ServeError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ServeError::NotFound => StatusCode::NOT_FOUND,
+            ServeError::InvalidUrl => StatusCode::BAD_REQUEST,
+            ServeError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ServeError::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ServeError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ServeError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ServeError::BuildFailed => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<&DocError> for ServeError {
+    fn from(err: &DocError) -> Self {
+        match err {
             DocError::RariIoError(_) | DocError::IOError(_) | DocError::PageNotFound(_, _) => {
-                (StatusCode::NOT_FOUND, "").into_response()
+                ServeError::NotFound
             }
+            DocError::UrlError(_) | DocError::LocaleError(_) => ServeError::InvalidUrl,
+            _ => ServeError::BuildFailed,
+        }
+    }
+}
 
-            _ => (StatusCode::INTERNAL_SERVER_ERROR, error!("🤷: {}", self.0)).into_response(),
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error_code: ServeError,
+    message: String,
+}
+
+fn error_response(error_code: ServeError, message: String) -> Response<Body> {
+    (
+        error_code.status_code(),
+        Json(ErrorResponse {
+            error_code,
+            message,
+        }),
+    )
+        .into_response()
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response<Body> {
+        let error_code = self.error_code();
+        let message = self.message();
+        if error_code == ServeError::BuildFailed {
+            error!("🤷: {message}");
         }
+        error_response(error_code, message)
     }
 }
 
@@ -173,22 +946,543 @@ where
     E: Into<DocError>,
 {
     fn from(err: E) -> Self {
-        Self(err.into())
+        Self::Doc(err.into())
     }
 }
 
-pub fn serve() -> Result<(), anyhow::Error> {
+/// Open `url` in the default browser, logging instead of failing if that doesn't work.
+fn open_browser(url: &str) {
+    let mut cmd = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    if let Err(e) = cmd.arg(url).spawn() {
+        tracing::warn!("unable to open {url} in browser: {e}");
+    }
+}
+
+/// The router with all layers enabled in `options`.
+fn app(options: ServeOptions) -> Result<Router, anyhow::Error> {
+    let log = options
+        .access_log
+        .as_ref()
+        .map(|path| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("unable to open access log {}", path.display()))
+        })
+        .transpose()?
+        .map(|file| Arc::new(Mutex::new(file)));
+    let basic_auth = options
+        .basic_auth
+        .as_deref()
+        .map(|credentials| {
+            if !credentials.contains(':') {
+                return Err(anyhow::anyhow!("basic auth credentials must be USER:PASS"));
+            }
+            Ok(Arc::<str>::from(credentials))
+        })
+        .transpose()?;
+    let security_headers = options.security_headers;
+    let max_concurrency = options.max_concurrency;
+    let rate_limiter = options
+        .rate_limit
+        .map(|rate| Arc::new(RateLimiter::new(rate)));
+    let app = Router::new()
+        .route("/", get(get_index_handler))
+        .route("/_search", get(get_search_handler))
+        .route("/_build", get(get_build_handler))
+        .route("/{locale}/search-index.json", get(get_search_index_handler))
+        .fallback(handler)
+        .with_state(Arc::new(options));
+    let app = if security_headers {
+        app.layer(map_response(add_security_headers))
+    } else {
+        app
+    };
+    // Added after the security headers, which only apply to the routes above.
+    let app = app.route("/_health", get(get_health_handler));
+    let app = if let Some(max) = max_concurrency {
+        app.layer(from_fn(move |req, next| limit_concurrency(max, req, next)))
+    } else {
+        app
+    };
+    let app = if let Some(expected) = basic_auth {
+        app.layer(from_fn(move |req, next| {
+            require_basic_auth(expected.clone(), req, next)
+        }))
+    } else {
+        app
+    };
+    let app = if let Some(limiter) = rate_limiter {
+        app.layer(from_fn(move |req, next| {
+            rate_limit(limiter.clone(), req, next)
+        }))
+    } else {
+        app
+    };
+    let app = if let Some(log) = log {
+        app.layer(from_fn(move |req, next| access_log(log.clone(), req, next)))
+    } else {
+        app
+    };
+    Ok(app)
+}
+
+pub(crate) fn serve(options: ServeOptions) -> Result<(), anyhow::Error> {
+    let open = options.open.clone();
+    let app = app(options)?;
     tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
         .unwrap()
         .block_on(async {
-            let app = Router::new()
-                .route("/{locale}/search-index.json", get(get_search_index_handler))
-                .fallback(handler);
-
             let listener = tokio::net::TcpListener::bind("0.0.0.0:8083").await.unwrap();
-            axum::serve(listener, app).await.unwrap();
+            if let Some(path) = open {
+                open_browser(&format!("http://localhost:8083{path}"));
+            }
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
         });
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+    use std::net::TcpStream;
+
+    use super::*;
+
+    /// Serve `app` on a free local port from a background thread.
+    fn spawn(app: Router) -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                    axum::serve(
+                        listener,
+                        app.into_make_service_with_connect_info::<SocketAddr>(),
+                    )
+                    .await
+                    .unwrap();
+                })
+        });
+        addr
+    }
+
+    /// The status code for `GET path`, sending the extra header lines in `headers`.
+    /// The raw response to `GET path`, with the extra header lines `headers`.
+    fn request(addr: SocketAddr, path: &str, headers: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{headers}\r\n"
+        )
+        .unwrap();
+        let mut res = String::new();
+        stream.read_to_string(&mut res).unwrap();
+        res
+    }
+
+    fn get_status(addr: SocketAddr, path: &str, headers: &str) -> u16 {
+        request(addr, path, headers)
+            .split(' ')
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    fn has_security_headers(res: &str) -> bool {
+        let res = res.to_lowercase();
+        SECURITY_HEADERS
+            .iter()
+            .all(|(name, value)| res.contains(&format!("{name}: {value}\r\n").to_lowercase()))
+    }
+
+    #[test]
+    fn test_security_headers() {
+        let addr = spawn(
+            app(ServeOptions {
+                security_headers: true,
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        assert!(has_security_headers(&request(addr, "/", "")));
+        assert!(has_security_headers(&request(
+            addr,
+            "/en-US/docs/Web/Nope/index.json",
+            ""
+        )));
+        assert!(!request(addr, "/_health", "")
+            .to_lowercase()
+            .contains("content-security-policy"));
+
+        let addr = spawn(app(ServeOptions::default()).unwrap());
+        assert!(!request(addr, "/", "")
+            .to_lowercase()
+            .contains("content-security-policy"));
+    }
+
+    #[test]
+    fn test_health_without_credentials() {
+        let addr = spawn(
+            app(ServeOptions {
+                basic_auth: Some("user:pass".to_string()),
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        assert_eq!(get_status(addr, "/_health", ""), 200);
+        assert_eq!(get_status(addr, "/", ""), 401);
+        let auth = format!(
+            "Authorization: Basic {}\r\n",
+            BASE64_STANDARD.encode("user:pass")
+        );
+        assert_eq!(get_status(addr, "/", &auth), 200);
+    }
+
+    #[test]
+    fn test_split_output_file() {
+        assert_eq!(
+            split_output_file("/en-US/docs/Web/HTML/index.json"),
+            ("/en-US/docs/Web/HTML", Some("index.json"))
+        );
+        assert_eq!(
+            split_output_file("/de/docs/Web/contributors.txt"),
+            ("/de/docs/Web", Some("contributors.txt"))
+        );
+        assert_eq!(
+            split_output_file("/en-US/docs/Web/HTML"),
+            ("/en-US/docs/Web/HTML", None)
+        );
+    }
+
+    fn item(title: &str, url: &str) -> SearchItem {
+        SearchItem {
+            title: title.to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_search_rank() {
+        let index = [
+            item("CSS grid layout", "/en-US/docs/Web/CSS/CSS_grid_layout"),
+            item("Grid", "/en-US/docs/Glossary/Grid"),
+            item("display", "/en-US/docs/Web/CSS/display"),
+            item("grid-area", "/en-US/docs/Web/CSS/grid-area"),
+            item("Gridlines", "/en-US/docs/Glossary/Grid_Lines"),
+            item("Flow layout", "/en-US/docs/Web/CSS/CSS_flow_layout"),
+        ];
+        let urls = |hits: Vec<SearchItem>| hits.into_iter().map(|hit| hit.url).collect::<Vec<_>>();
+        assert_eq!(
+            urls(search(&index, "GRID", 10)),
+            [
+                "/en-US/docs/Glossary/Grid",
+                "/en-US/docs/Web/CSS/grid-area",
+                "/en-US/docs/Glossary/Grid_Lines",
+                "/en-US/docs/Web/CSS/CSS_grid_layout",
+            ]
+        );
+        assert_eq!(
+            urls(search(&index, "css_", 10)),
+            [
+                "/en-US/docs/Web/CSS/CSS_grid_layout",
+                "/en-US/docs/Web/CSS/CSS_flow_layout",
+            ]
+        );
+        assert!(search(&index, "table", 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_limit() {
+        let index = [
+            item("grid-area", "/en-US/docs/Web/CSS/grid-area"),
+            item("grid-row", "/en-US/docs/Web/CSS/grid-row"),
+            item("Grid", "/en-US/docs/Glossary/Grid"),
+        ];
+        assert_eq!(
+            search(&index, "grid", 2),
+            [item("Grid", "/en-US/docs/Glossary/Grid"), index[0].clone()]
+        );
+        assert!(search(&index, "grid", 0).is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("grid", ""), 4);
+        assert_eq!(edit_distance("", "grid"), 4);
+        assert_eq!(edit_distance("grid", "grid"), 0);
+        assert_eq!(edit_distance("grid", "gird"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("Ärger", "Arger"), 1);
+    }
+
+    #[test]
+    fn test_prefers_html() {
+        let accept = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+            headers
+        };
+        assert!(prefers_html(&accept(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+        )));
+        assert!(prefers_html(&accept("text/html;q=0.9, application/json")));
+        assert!(!prefers_html(&accept("application/json, text/html")));
+        assert!(!prefers_html(&accept("application/ld+json, text/html")));
+        assert!(!prefers_html(&accept("*/*")));
+        assert!(!prefers_html(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_closest_url() {
+        let known = [
+            "/en-US/docs/Web/HTML",
+            "/en-US/docs/Web/HTML/Element/a",
+            "/en-US/docs/Web/CSS/display",
+        ];
+        let closest = |url| closest_url(url, known.into_iter());
+        assert_eq!(
+            closest("/en-US/docs/Web/HTML"),
+            Some("/en-US/docs/Web/HTML")
+        );
+        assert_eq!(
+            closest("/en-us/docs/web/html"),
+            Some("/en-US/docs/Web/HTML")
+        );
+        assert_eq!(
+            closest("/en-US/docs/Web/HTML/Elemnt/a"),
+            Some("/en-US/docs/Web/HTML/Element/a")
+        );
+        assert_eq!(closest("/en-US/docs/Web/JavaScript"), None);
+        // 16 chars allow 4 edits.
+        assert_eq!(
+            closest_url("/abcdefghijklmno", ["/abcdefghijklmnoWXYZ"].into_iter()),
+            Some("/abcdefghijklmnoWXYZ")
+        );
+        assert_eq!(
+            closest_url("/abcdefghijklmno", ["/abcdefghijklmnoVWXYZ"].into_iter()),
+            None
+        );
+        // Short urls still allow 2 edits.
+        assert_eq!(closest_url("/ab", ["/abcd"].into_iter()), Some("/abcd"));
+        assert_eq!(closest_url("/ab", ["/abcde"].into_iter()), None);
+    }
+
+    #[test]
+    fn test_rate_limiter_burst() {
+        let limiter = RateLimiter::new(3);
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire(ip, now), Ok(()));
+        }
+        assert_eq!(limiter.acquire(ip, now), Err(1));
+        // Other clients have their own bucket.
+        assert_eq!(limiter.acquire(IpAddr::from([127, 0, 0, 2]), now), Ok(()));
+    }
+
+    #[test]
+    fn test_rate_limiter_refill() {
+        let limiter = RateLimiter::new(2);
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let now = Instant::now();
+        assert_eq!(limiter.acquire(ip, now), Ok(()));
+        assert_eq!(limiter.acquire(ip, now), Ok(()));
+        assert_eq!(limiter.acquire(ip, now), Err(1));
+        // Half a second refills one token.
+        let now = now + Duration::from_millis(500);
+        assert_eq!(limiter.acquire(ip, now), Ok(()));
+        assert_eq!(limiter.acquire(ip, now), Err(1));
+        // Idling doesn't save up more than a second worth of requests.
+        let now = now + Duration::from_secs(10);
+        assert_eq!(limiter.acquire(ip, now), Ok(()));
+        assert_eq!(limiter.acquire(ip, now), Ok(()));
+        assert_eq!(limiter.acquire(ip, now), Err(1));
+    }
+
+    #[test]
+    fn test_rate_limit_retry_after() {
+        let app = app(ServeOptions {
+            rate_limit: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let addr = spawn(app);
+        assert_eq!(get_status(addr, "/", ""), 200);
+        let res = request(addr, "/", "");
+        assert_eq!(res.split(' ').nth(1), Some("429"));
+        assert!(res.to_lowercase().contains("retry-after: 1\r\n"));
+        // Health checks aren't limited.
+        assert_eq!(get_status(addr, "/_health", ""), 200);
+    }
+
+    #[test]
+    fn test_rate_limiter_sweep() {
+        let limiter = RateLimiter::new(1);
+        let start = *limiter.swept.lock().unwrap();
+        let fill = |now| {
+            for i in 0..=MAX_BUCKETS as u32 {
+                let _ = limiter.acquire(IpAddr::from((i + 1).to_be_bytes()), now);
+            }
+        };
+        fill(start);
+        // Within the interval nothing is swept, even though all buckets are full again.
+        let now = start + Duration::from_secs(2);
+        let _ = limiter.acquire(IpAddr::from([127, 0, 0, 1]), now);
+        assert_eq!(limiter.buckets.len(), MAX_BUCKETS + 2);
+        let now = start + SWEEP_INTERVAL;
+        let _ = limiter.acquire(IpAddr::from([127, 0, 0, 1]), now);
+        assert_eq!(limiter.buckets.len(), 1);
+    }
+
+    #[test]
+    fn test_normalized_location() {
+        for (path, location) in [
+            ("/en-us/docs/Web/HTML", Some("/en-US/docs/Web/HTML")),
+            ("/pt-br/docs/Web/HTML", Some("/pt-BR/docs/Web/HTML")),
+            ("/en-US/docs/Web/HTML/", Some("/en-US/docs/Web/HTML")),
+            ("/en-us/docs/Web/HTML/", Some("/en-US/docs/Web/HTML")),
+            (
+                "/en-us/docs/Web/index.json",
+                Some("/en-US/docs/Web/index.json"),
+            ),
+            (
+                "/en-us/docs/Web/contributors.txt",
+                Some("/en-US/docs/Web/contributors.txt"),
+            ),
+            // Already canonical.
+            ("/en-US/docs/Web/HTML", None),
+            ("/en-US/docs/Web/index.json", None),
+            // Unknown paths are left to the handler.
+            ("/", None),
+            ("/nope", None),
+            ("/xx-yy/docs/Web", None),
+            ("/_search", None),
+        ] {
+            assert_eq!(
+                normalized_location(path).as_deref(),
+                location,
+                "normalized_location({path:?})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_redirect_keeps_query() {
+        let addr = spawn(app(ServeOptions::default()).unwrap());
+        let res = request(addr, "/en-us/docs/Web/HTML/?nocache=1&root=a", "");
+        assert_eq!(res.split(' ').nth(1), Some("301"));
+        // hyper sends lowercase header names.
+        assert!(res.contains("\r\nlocation: /en-US/docs/Web/HTML?nocache=1&root=a\r\n"));
+    }
+
+    #[test]
+    fn test_basic_credentials() {
+        let credentials = |value| basic_credentials(&HeaderValue::from_static(value));
+        assert_eq!(
+            credentials("Basic dXNlcjpwYXNz").as_deref(),
+            Some(&b"user:pass"[..])
+        );
+        assert_eq!(
+            credentials("bAsIc  dXNlcjpwYXNz ").as_deref(),
+            Some(&b"user:pass"[..])
+        );
+        assert_eq!(credentials("Bearer dXNlcjpwYXNz"), None);
+        assert_eq!(credentials("Basic not base64!"), None);
+        assert_eq!(credentials("Basic"), None);
+    }
+
+    #[test]
+    fn test_basic_auth() {
+        let addr = spawn(
+            app(ServeOptions {
+                basic_auth: Some("user:pass".to_string()),
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let res = request(addr, "/", "");
+        assert_eq!(res.split(' ').nth(1), Some("401"));
+        assert!(res.contains("\r\nwww-authenticate: Basic realm=\"rari\", charset=\"UTF-8\"\r\n"));
+        let auth = |scheme: &str, credentials: &str| {
+            format!(
+                "Authorization: {scheme} {}\r\n",
+                BASE64_STANDARD.encode(credentials)
+            )
+        };
+        assert_eq!(get_status(addr, "/", &auth("Basic", "user:pass")), 200);
+        assert_eq!(get_status(addr, "/", &auth("basic", "user:pass")), 200);
+        assert_eq!(get_status(addr, "/", &auth("BASIC", "user:pass")), 200);
+        assert_eq!(get_status(addr, "/", &auth("Basic", "user:wrong")), 401);
+        assert_eq!(get_status(addr, "/", &auth("Basic", "user:pas")), 401);
+        assert_eq!(get_status(addr, "/", &auth("Bearer", "user:pass")), 401);
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        let err = catch_panic::<()>(|| panic!("boom")).unwrap_err();
+        assert!(matches!(&err, AppError::Panic(message) if message == "boom"));
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert!(matches!(catch_panic(|| Ok(1)), Ok(1)));
+    }
+
+    #[test]
+    fn test_serve_after_panic() {
+        let addr = spawn(
+            Router::new()
+                .route(
+                    "/panic",
+                    get(|| async { catch_panic::<&str>(|| panic!("boom")) }),
+                )
+                .route("/ok", get(|| async { "ok" })),
+        );
+        assert_eq!(get_status(addr, "/panic", ""), 500);
+        assert_eq!(get_status(addr, "/ok", ""), 200);
+        assert_eq!(get_status(addr, "/panic", ""), 500);
+    }
+
+    #[test]
+    fn test_limit_concurrency() {
+        let addr = spawn(
+            Router::new()
+                .route(
+                    "/slow",
+                    get(|| async {
+                        blocking(|| std::thread::sleep(std::time::Duration::from_millis(500))).await
+                    }),
+                )
+                .layer(from_fn(|req, next| limit_concurrency(1, req, next))),
+        );
+        let slow = std::thread::spawn(move || get_status(addr, "/slow", ""));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(get_status(addr, "/slow", ""), 503);
+        assert_eq!(slow.join().unwrap(), 200);
+        assert_eq!(get_status(addr, "/slow", ""), 200);
+    }
+}
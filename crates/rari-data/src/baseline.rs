@@ -1,10 +1,15 @@
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::fs;
 use std::marker::PhantomData;
 use std::path::Path;
 
 use indexmap::IndexMap;
 use rari_utils::concat_strs;
+use rari_utils::error::RariIoError;
+use rari_utils::html::html_to_text;
 use rari_utils::io::read_to_string;
 use schemars::JsonSchema;
 use serde::de::{self, value, SeqAccess, Visitor};
@@ -22,10 +27,34 @@ pub struct Baseline<'a> {
     pub asterisk: bool,
 }
 
+/// Deserializes like the web-features data (the serialized `bcd_keys` are ignored), with the
+/// indexes rebuilt by [`WebFeatures::from_features`].
 #[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(from = "SerializedWebFeatures")]
 pub struct WebFeatures {
-    pub features: IndexMap<String, FeatureData>,
-    pub bcd_keys: Vec<KeyStatus>,
+    /// Use [`WebFeatures::iter`] and [`WebFeatures::get`], the indexes below are built from this.
+    features: IndexMap<String, FeatureData>,
+    bcd_keys: Vec<KeyStatus>,
+    /// Normalized spec url → keys of the features referencing it.
+    #[serde(skip)]
+    spec_index: HashMap<String, Vec<String>>,
+    /// Snapshot slug → keys of the features in that snapshot.
+    #[serde(skip)]
+    snapshot_index: HashMap<String, Vec<String>>,
+    /// All BCD keys in `bcd_keys` order, with dots.
+    #[serde(skip)]
+    compat_keys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SerializedWebFeatures {
+    features: IndexMap<String, FeatureData>,
+}
+
+impl From<SerializedWebFeatures> for WebFeatures {
+    fn from(SerializedWebFeatures { features }: SerializedWebFeatures) -> Self {
+        Self::from_features(features)
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -49,22 +78,107 @@ fn unspaced(bcd_key: &str) -> String {
     bcd_key.replace(' ', ".")
 }
 
+/// Normalize a spec url for lookups by dropping the fragment and trailing slashes.
+fn normalize_spec_url(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.as_str().trim_end_matches('/').to_string()
+}
+
+/// Domains hosting specifications. Subdomains match too, so `w3.org` covers `www.w3.org`.
+const SPEC_HOSTS: &[&str] = &[
+    "w3.org",
+    "whatwg.org",
+    "tc39.es",
+    "csswg.org",
+    "fxtf.org",
+    "css-houdini.org",
+    "ietf.org",
+    "rfc-editor.org",
+    "httpwg.org",
+    "khronos.org",
+    "w3c.github.io",
+    "wicg.github.io",
+    "webassembly.github.io",
+    "webaudio.github.io",
+    "gpuweb.github.io",
+    "immersive-web.github.io",
+    "privacycg.github.io",
+    "webmachinelearning.github.io",
+];
+
+fn is_spec_host(url: &Url) -> bool {
+    url.host_str().is_some_and(|host| {
+        SPEC_HOSTS.iter().any(|spec_host| {
+            host == *spec_host
+                || host
+                    .strip_suffix(spec_host)
+                    .is_some_and(|sub| sub.ends_with('.'))
+        })
+    })
+}
+
+/// Parses a web-features JSON string, skipping (and logging) features that don't deserialize.
+fn clean_features(json_str: &str) -> Result<IndexMap<String, FeatureData>, Error> {
+    let dirty_map: DirtyWebFeatures = serde_json::from_str(json_str)?;
+    Ok(dirty_map
+        .features
+        .into_iter()
+        .filter_map(|(k, v)| {
+            serde_json::from_value::<FeatureData>(v)
+                .inspect_err(|e| tracing::error!("Error serializing baseline for {}: {}", k, &e))
+                .ok()
+                .map(|v| (k, v))
+        })
+        .collect())
+}
+
 impl WebFeatures {
     pub fn from_file(path: &Path) -> Result<Self, Error> {
         let json_str = read_to_string(path)?;
-        let dirty_map: DirtyWebFeatures = serde_json::from_str(&json_str)?;
-        let features: IndexMap<String, FeatureData> = dirty_map
-            .features
-            .into_iter()
-            .filter_map(|(k, v)| {
-                serde_json::from_value::<FeatureData>(v)
-                    .inspect_err(|e| {
-                        tracing::error!("Error serializing baseline for {}: {}", k, &e)
-                    })
-                    .ok()
-                    .map(|v| (k, v))
-            })
-            .collect();
+        Self::from_json_str(&json_str)
+    }
+
+    /// Parses web-features data from a JSON string, skipping (and logging) features
+    /// that don't deserialize.
+    pub fn from_json_str(json_str: &str) -> Result<Self, Error> {
+        Ok(Self::from_features(clean_features(json_str)?))
+    }
+
+    /// Reads and merges every `*.json` file in `path` (non-recursive).
+    ///
+    /// Files are merged in file name order. If a feature key shows up in several files,
+    /// the later file wins and the collision is logged.
+    pub fn from_dir(path: &Path) -> Result<Self, Error> {
+        let io_err = |source| RariIoError {
+            source,
+            path: path.to_path_buf(),
+        };
+        let mut files = fs::read_dir(path)
+            .map_err(io_err)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_err)?;
+        files.retain(|file| file.is_file() && file.extension().is_some_and(|ext| ext == "json"));
+        files.sort();
+
+        let mut features: IndexMap<String, FeatureData> = IndexMap::new();
+        for file in files {
+            for (k, v) in clean_features(&read_to_string(&file)?)? {
+                if features.insert(k.clone(), v).is_some() {
+                    tracing::warn!(
+                        "Duplicate baseline feature {} overridden by {}",
+                        k,
+                        file.display()
+                    );
+                }
+            }
+        }
+        Ok(Self::from_features(features))
+    }
+
+    /// Build all lookup indexes for the given features.
+    fn from_features(features: IndexMap<String, FeatureData>) -> Self {
         // bcd_keys is a sorted by KeyStatus.bcd_key
         // We replace "." with " " so the sorting is stable as in:
         // http headers Content-Security-Policy
@@ -89,11 +203,186 @@ impl WebFeatures {
                 })
             })
             .collect();
-        bcd_keys.sort_by(|a, b| a.bcd_key.cmp(&b.bcd_key));
+        // If several features claim a key, the first one after sorting wins:
+        // non-discouraged features before discouraged ones, then by feature key.
+        let discouraged = |ks: &KeyStatus| {
+            features
+                .get(&ks.feature)
+                .is_some_and(|fd| fd.discouraged.is_some())
+        };
+        bcd_keys.sort_by(|a, b| {
+            a.bcd_key
+                .cmp(&b.bcd_key)
+                .then_with(|| discouraged(a).cmp(&discouraged(b)))
+                .then_with(|| a.feature.cmp(&b.feature))
+        });
         bcd_keys.dedup_by(|a, b| a.bcd_key == b.bcd_key);
 
-        let map = WebFeatures { features, bcd_keys };
-        Ok(map)
+        let mut spec_index: HashMap<String, Vec<String>> = HashMap::new();
+        for (feature, fd) in &features {
+            for spec in &fd.spec {
+                let entry = spec_index.entry(normalize_spec_url(spec)).or_default();
+                if !entry.contains(feature) {
+                    entry.push(feature.clone());
+                }
+            }
+        }
+
+        let mut snapshot_index: HashMap<String, Vec<String>> = HashMap::new();
+        for (feature, fd) in &features {
+            for snapshot in &fd.snapshot {
+                snapshot_index
+                    .entry(snapshot.clone())
+                    .or_default()
+                    .push(feature.clone());
+            }
+        }
+
+        let compat_keys = bcd_keys.iter().map(|ks| unspaced(&ks.bcd_key)).collect();
+
+        WebFeatures {
+            features,
+            bcd_keys,
+            spec_index,
+            snapshot_index,
+            compat_keys,
+        }
+    }
+
+    /// Returns all features as `(key, data)` in dataset order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FeatureData)> {
+        self.features.iter().map(|(k, fd)| (k.as_str(), fd))
+    }
+
+    /// Returns the feature with the key `feature`, e.g. `grid`.
+    pub fn get(&self, feature: &str) -> Option<&FeatureData> {
+        self.features.get(feature)
+    }
+
+    /// Returns the number of features.
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Returns every BCD key listed in any feature's `compat_features`, deduplicated.
+    ///
+    /// Keys are ordered by path segments, so each key is directly followed by its subkeys:
+    /// `http.headers.Content-Security-Policy.base-uri` comes before
+    /// `http.headers.Content-Security-Policy-Report-Only`.
+    pub fn all_compat_keys(&self) -> impl Iterator<Item = &str> {
+        self.compat_keys.iter().map(String::as_str)
+    }
+
+    /// Returns the keys of all features in the snapshot `snapshot` (e.g. `ecmascript-2020`),
+    /// in dataset order.
+    pub fn features_in_snapshot(&self, snapshot: &str) -> Vec<&str> {
+        self.snapshot_index
+            .get(snapshot)
+            .map(|features| features.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the keys of all features referencing `spec`, in dataset order.
+    ///
+    /// Fragments and trailing slashes are ignored when matching, so
+    /// `https://drafts.csswg.org/css-grid/#subgrids` matches `https://drafts.csswg.org/css-grid/`.
+    pub fn features_for_spec(&self, spec: &Url) -> Vec<&str> {
+        self.spec_index
+            .get(&normalize_spec_url(spec))
+            .map(|features| features.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns `(feature, spec url)` for every spec url not hosted on a known
+    /// specification domain, in dataset order.
+    ///
+    /// This is a data quality check for typos and stale links, it's not run when loading.
+    pub fn suspicious_spec_urls(&self) -> Vec<(String, &Url)> {
+        self.features
+            .iter()
+            .flat_map(|(feature, fd)| {
+                fd.spec
+                    .iter()
+                    .filter(|spec| !is_spec_host(spec))
+                    .map(|spec| (feature.clone(), spec))
+            })
+            .collect()
+    }
+
+    /// Returns the features whose Baseline status differs from `older`, in dataset order.
+    ///
+    /// Features missing from either dataset are ignored, a missing status counts as not Baseline.
+    pub fn diff(&self, older: &WebFeatures) -> Vec<BaselineChange> {
+        let baseline = |fd: &FeatureData| {
+            fd.status
+                .as_ref()
+                .and_then(|status| status.baseline)
+                .unwrap_or(BaselineHighLow::False(false))
+        };
+        self.features
+            .iter()
+            .filter_map(|(feature, fd)| {
+                let old = baseline(older.features.get(feature)?);
+                let new = baseline(fd);
+                (old.rank() != new.rank()).then(|| BaselineChange {
+                    feature: feature.clone(),
+                    old,
+                    new,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns all BCD keys listed in the `compat_features` of more than one feature,
+    /// together with those features, sorted by key.
+    ///
+    /// For these keys [`WebFeatures::feature_status`] picks the first non-discouraged
+    /// feature by key.
+    pub fn duplicate_compat_keys(&self) -> Vec<(String, Vec<String>)> {
+        let mut claimed: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (feature, fd) in &self.features {
+            for bcd_key in &fd.compat_features {
+                let features = claimed.entry(bcd_key).or_default();
+                if !features.contains(feature) {
+                    features.push(feature.clone());
+                }
+            }
+        }
+        claimed
+            .into_iter()
+            .filter(|(_, features)| features.len() > 1)
+            .map(|(bcd_key, features)| (bcd_key.to_string(), features))
+            .collect()
+    }
+
+    /// Returns the keys from `keys` that aren't listed in any feature's `compat_features`.
+    ///
+    /// Unlike [`WebFeatures::feature_status`] returning `None`, this only flags keys
+    /// the dataset doesn't know about at all.
+    pub fn unmatched_keys<'a>(&self, keys: &[&'a str]) -> Vec<&'a str> {
+        keys.iter()
+            .filter(|bcd_key| {
+                let bcd_key_spaced = spaced(bcd_key);
+                self.bcd_keys
+                    .binary_search_by(|ks| ks.bcd_key.as_str().cmp(&bcd_key_spaced))
+                    .is_err()
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns the key of the feature `bcd_key` is attributed to, even if
+    /// [`WebFeatures::feature_status`] has no status for it.
+    pub fn feature_for_compat_key(&self, bcd_key: &str) -> Option<&str> {
+        let bcd_key_spaced = spaced(bcd_key);
+        self.bcd_keys
+            .binary_search_by(|ks| ks.bcd_key.as_str().cmp(&bcd_key_spaced))
+            .ok()
+            .map(|i| self.bcd_keys[i].feature.as_str())
     }
 
     pub fn sub_keys(&self, bcd_key: &str) -> &[KeyStatus] {
@@ -116,6 +405,9 @@ impl WebFeatures {
 
     // Compute status according to:
     // https://github.com/mdn/yari/issues/11546#issuecomment-2531611136
+    //
+    // Keys claimed by several features resolve to the first non-discouraged
+    // feature by key, see `duplicate_compat_keys`.
     pub fn feature_status(&self, bcd_key: &str) -> Option<Baseline> {
         let bcd_key_spaced = &spaced(bcd_key);
         if let Some(status) = self.feature_status_internal(bcd_key_spaced) {
@@ -242,7 +534,9 @@ pub struct FeatureData {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub compat_features: Vec<String>,
+    #[serde(default)]
     pub description: String,
+    #[serde(default)]
     pub description_html: String,
     #[serde(
         deserialize_with = "t_or_vec",
@@ -262,6 +556,33 @@ pub struct FeatureData {
     pub discouraged: Option<Discouraged>,
 }
 
+impl FeatureData {
+    /// The plain text description, derived from `description_html` by stripping
+    /// tags and decoding entities if `description` is missing.
+    pub fn description(&self) -> Cow<'_, str> {
+        if self.description.is_empty() {
+            Cow::Owned(html_to_text(&self.description_html))
+        } else {
+            Cow::Borrowed(&self.description)
+        }
+    }
+
+    /// The html description, derived from `description` by escaping it if
+    /// `description_html` is missing.
+    pub fn description_html(&self) -> Cow<'_, str> {
+        if self.description_html.is_empty() {
+            Cow::Owned(
+                self.description
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;"),
+            )
+        } else {
+            Cow::Borrowed(&self.description_html)
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Discouraged {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -281,25 +602,158 @@ pub enum BrowserIdentifier {
     Safari,
     SafariIos,
 }
+
+impl BrowserIdentifier {
+    /// All browsers in display order, desktop before mobile.
+    pub const ALL: [BrowserIdentifier; 7] = [
+        BrowserIdentifier::Chrome,
+        BrowserIdentifier::Edge,
+        BrowserIdentifier::Firefox,
+        BrowserIdentifier::Safari,
+        BrowserIdentifier::ChromeAndroid,
+        BrowserIdentifier::FirefoxAndroid,
+        BrowserIdentifier::SafariIos,
+    ];
+
+    /// Human-readable browser name, e.g. `Safari on iOS`.
+    pub fn friendly_name(&self) -> &'static str {
+        match self {
+            BrowserIdentifier::Chrome => "Chrome",
+            BrowserIdentifier::ChromeAndroid => "Chrome Android",
+            BrowserIdentifier::Edge => "Edge",
+            BrowserIdentifier::Firefox => "Firefox",
+            BrowserIdentifier::FirefoxAndroid => "Firefox for Android",
+            BrowserIdentifier::Safari => "Safari",
+            BrowserIdentifier::SafariIos => "Safari on iOS",
+        }
+    }
+}
+
 #[derive(
-    Deserialize, Serialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
+    Deserialize, Serialize, Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
 )]
 pub struct Support {
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    chrome: Option<String>,
+    chrome: Option<SupportVersion>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    chrome_android: Option<String>,
+    chrome_android: Option<SupportVersion>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    edge: Option<String>,
+    edge: Option<SupportVersion>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    firefox: Option<String>,
+    firefox: Option<SupportVersion>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    firefox_android: Option<String>,
+    firefox_android: Option<SupportVersion>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    safari: Option<String>,
+    safari: Option<SupportVersion>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    safari_ios: Option<String>,
+    safari_ios: Option<SupportVersion>,
+    /// Browsers web-features added after this list was written, kept so they
+    /// don't fail deserialization and survive a round trip.
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    other: BTreeMap<String, SupportVersion>,
 }
+impl Support {
+    /// Whether no browser version is listed at all, which web-features data can omit.
+    pub fn is_empty(&self) -> bool {
+        BrowserIdentifier::ALL
+            .iter()
+            .all(|browser| self.get(*browser).is_none())
+            && self.other.is_empty()
+    }
+
+    pub fn get(&self, browser: BrowserIdentifier) -> Option<&SupportVersion> {
+        match browser {
+            BrowserIdentifier::Chrome => self.chrome.as_ref(),
+            BrowserIdentifier::ChromeAndroid => self.chrome_android.as_ref(),
+            BrowserIdentifier::Edge => self.edge.as_ref(),
+            BrowserIdentifier::Firefox => self.firefox.as_ref(),
+            BrowserIdentifier::FirefoxAndroid => self.firefox_android.as_ref(),
+            BrowserIdentifier::Safari => self.safari.as_ref(),
+            BrowserIdentifier::SafariIos => self.safari_ios.as_ref(),
+        }
+    }
+
+    /// Terse support line like `Chrome 111, Firefox 110, Safari ≤16.4`.
+    ///
+    /// Browsers follow [`BrowserIdentifier::ALL`] (desktop before mobile), browsers
+    /// unknown to rari come last by key. Unsupported browsers are left out.
+    pub fn summary(&self) -> String {
+        BrowserIdentifier::ALL
+            .iter()
+            .filter_map(|browser| {
+                self.get(*browser)
+                    .map(|version| format!("{} {version}", browser.friendly_name()))
+            })
+            .chain(
+                self.other
+                    .iter()
+                    .map(|(browser, version)| format!("{browser} {version}")),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A browser version from the support map, keeping the qualifiers web-features uses.
+///
+/// (De)serializes from and to the original textual form, e.g. `"120"`, `"≤18"` or `"preview"`.
+#[derive(Deserialize, Serialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(from = "String", into = "String")]
+pub enum SupportVersion {
+    /// Supported since exactly this version.
+    Exact(String),
+    /// Supported in this version or an earlier one (`≤` prefix).
+    OrEarlier(String),
+    /// Only supported in preview releases.
+    Preview,
+}
+
+impl From<String> for SupportVersion {
+    fn from(s: String) -> Self {
+        if s == "preview" {
+            SupportVersion::Preview
+        } else if let Some(version) = s.strip_prefix('≤') {
+            SupportVersion::OrEarlier(version.to_string())
+        } else {
+            SupportVersion::Exact(s)
+        }
+    }
+}
+
+impl From<SupportVersion> for String {
+    fn from(version: SupportVersion) -> Self {
+        match version {
+            SupportVersion::Exact(version) => version,
+            SupportVersion::OrEarlier(version) => concat_strs!("≤", &version),
+            SupportVersion::Preview => "preview".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SupportVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SupportVersion::Exact(version) => f.write_str(version),
+            SupportVersion::OrEarlier(version) => write!(f, "≤{version}"),
+            SupportVersion::Preview => f.write_str("preview"),
+        }
+    }
+}
+
+impl JsonSchema for SupportVersion {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum BaselineHighLow {
@@ -309,6 +763,130 @@ pub enum BaselineHighLow {
     False(bool),
 }
 
+impl BaselineHighLow {
+    /// Rank of the status in the order `False < Low < High`.
+    ///
+    /// web-features only ever emits `false`, so both `False(false)` and
+    /// `False(true)` rank as not Baseline.
+    pub fn rank(&self) -> u8 {
+        match self {
+            BaselineHighLow::False(_) => 0,
+            BaselineHighLow::Low => 1,
+            BaselineHighLow::High => 2,
+        }
+    }
+
+    /// Whether the status is at least Baseline Low.
+    pub fn is_baseline(&self) -> bool {
+        self.rank() > 0
+    }
+}
+
+impl PartialOrd for BaselineHighLow {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BaselineHighLow {
+    // Orders by `rank`. `False(false)` sorts before `False(true)` so that
+    // the ordering stays consistent with `Eq`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (BaselineHighLow::False(a), BaselineHighLow::False(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// A feature whose Baseline status changed between two datasets, see [`WebFeatures::diff`].
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BaselineChange {
+    pub feature: String,
+    pub old: BaselineHighLow,
+    pub new: BaselineHighLow,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BaselineChangeKind {
+    /// Not Baseline before, Baseline now.
+    NewlyBaseline,
+    /// Baseline Low before, Baseline High now.
+    Promoted,
+    /// Lower status than before.
+    Regressed,
+}
+
+impl BaselineChange {
+    pub fn kind(&self) -> BaselineChangeKind {
+        if self.new < self.old {
+            BaselineChangeKind::Regressed
+        } else if self.old.is_baseline() {
+            BaselineChangeKind::Promoted
+        } else {
+            BaselineChangeKind::NewlyBaseline
+        }
+    }
+}
+
+/// The group hierarchy of web-features, as published in the `groups` of its `data.json`.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct WebFeatureGroups {
+    pub groups: IndexMap<String, GroupData>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GroupData {
+    pub name: String,
+    /// Key of the enclosing group, `None` for top-level groups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// A group in a [`WebFeatureGroups::group_path`].
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupRef<'a> {
+    pub key: &'a str,
+    pub name: &'a str,
+}
+
+impl WebFeatureGroups {
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let json_str = read_to_string(path)?;
+        Self::from_json_str(&json_str)
+    }
+
+    /// Parses the `groups` of a web-features JSON string, other top-level keys are ignored.
+    pub fn from_json_str(json_str: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+
+    /// Returns the ancestry of `group`, top-level group first and `group` itself last.
+    ///
+    /// Unknown groups yield an empty path. An unknown or cyclic parent ends the path there.
+    pub fn group_path(&self, group: &str) -> Vec<GroupRef<'_>> {
+        let mut path = vec![];
+        let mut next = Some(group);
+        while let Some(key) = next {
+            let Some((key, data)) = self.groups.get_key_value(key) else {
+                break;
+            };
+            if path.iter().any(|group: &GroupRef| group.key == key) {
+                tracing::warn!("Cycle in web-features groups at {}", key);
+                break;
+            }
+            path.push(GroupRef {
+                key,
+                name: &data.name,
+            });
+            next = data.parent.as_deref();
+        }
+        path.reverse();
+        path
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct SupportStatus {
     /// Whether the feature is Baseline (low substatus), Baseline (high substatus), or not (false)
@@ -320,7 +898,8 @@ pub struct SupportStatus {
     /// Date the feature achieved Baseline high status
     #[serde(skip_serializing_if = "Option::is_none")]
     pub baseline_high_date: Option<String>,
-    /// Browser versions that most-recently introduced the feature
+    /// Browser versions that most-recently introduced the feature, may be empty
+    #[serde(default)]
     pub support: Support,
 }
 
@@ -335,12 +914,39 @@ pub struct SupportStatusWithByKey {
     /// Date the feature achieved Baseline high status
     #[serde(skip_serializing_if = "Option::is_none")]
     pub baseline_high_date: Option<String>,
-    /// Browser versions that most-recently introduced the feature
+    /// Browser versions that most-recently introduced the feature, may be empty
+    #[serde(default)]
     pub support: Support,
     #[serde(default, skip_serializing)]
     pub by_compat_key: Option<BTreeMap<String, SupportStatus>>,
 }
 
+impl SupportStatus {
+    /// See [`Support::summary`].
+    pub fn support_summary(&self) -> String {
+        self.support.summary()
+    }
+}
+
+impl SupportStatusWithByKey {
+    /// See [`Support::summary`].
+    pub fn support_summary(&self) -> String {
+        self.support.summary()
+    }
+
+    /// Year the feature reached Baseline high, or Baseline low if it is not high yet.
+    ///
+    /// Ranged dates like `≤2020-03-24` yield the year of the bound.
+    pub fn baseline_year(&self) -> Option<i32> {
+        let date = self
+            .baseline_high_date
+            .as_deref()
+            .or(self.baseline_low_date.as_deref())?;
+        let date = date.trim_start_matches('≤');
+        date.split('-').next()?.parse().ok()
+    }
+}
+
 pub fn t_or_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
 where
     D: Deserializer<'de>,
@@ -377,3 +983,690 @@ where
 
     deserializer.deserialize_any(TOrVec::<T>(PhantomData))
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn feature(value: Value) -> FeatureData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_from_json_str() {
+        let web_features = WebFeatures::from_json_str(
+            &json!({
+                "features": {
+                    "grid": {
+                        "name": "Grid",
+                        "description": "Grid",
+                        "description_html": "Grid",
+                        "compat_features": "css.properties.grid",
+                    },
+                    "broken": {
+                        "description": "Missing a name",
+                    },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            web_features.features.keys().collect::<Vec<_>>(),
+            vec!["grid"]
+        );
+        assert!(web_features
+            .unmatched_keys(&["css.properties.grid"])
+            .is_empty());
+        assert!(WebFeatures::from_json_str("{}").is_err());
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let dir = std::env::temp_dir().join(format!("rari-web-features-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("a.json"),
+            json!({
+                "features": {
+                    "grid": {
+                        "name": "Grid",
+                        "compat_features": ["css.properties.grid"],
+                    },
+                    "subgrid": {
+                        "name": "Subgrid (a)",
+                        "compat_features": ["css.properties.grid-template-columns.subgrid"],
+                    },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("b.json"),
+            json!({
+                "features": {
+                    "flexbox": {
+                        "name": "Flexbox",
+                        "compat_features": ["css.properties.display.flex"],
+                    },
+                    "subgrid": {
+                        "name": "Subgrid (b)",
+                        "compat_features": ["css.properties.grid-template-columns.subgrid"],
+                    },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "not json").unwrap();
+
+        let web_features = WebFeatures::from_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let web_features = web_features.unwrap();
+
+        assert_eq!(
+            web_features.features.keys().collect::<Vec<_>>(),
+            vec!["grid", "subgrid", "flexbox"]
+        );
+        assert_eq!(web_features.features["subgrid"].name, "Subgrid (b)");
+        assert_eq!(
+            web_features.all_compat_keys().collect::<Vec<_>>(),
+            vec![
+                "css.properties.display.flex",
+                "css.properties.grid",
+                "css.properties.grid-template-columns.subgrid",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_path() {
+        let groups = WebFeatureGroups::from_json_str(
+            &json!({
+                "features": {},
+                "groups": {
+                    "css": { "name": "CSS" },
+                    "grid": { "name": "Grid", "parent": "css" },
+                    "subgrid": { "name": "Subgrid", "parent": "grid" },
+                    "orphan": { "name": "Orphan", "parent": "missing" },
+                    "loop": { "name": "Loop", "parent": "loop" },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            groups.group_path("subgrid"),
+            vec![
+                GroupRef {
+                    key: "css",
+                    name: "CSS"
+                },
+                GroupRef {
+                    key: "grid",
+                    name: "Grid"
+                },
+                GroupRef {
+                    key: "subgrid",
+                    name: "Subgrid"
+                },
+            ]
+        );
+        assert_eq!(groups.group_path("css").len(), 1);
+        assert_eq!(
+            groups.group_path("orphan"),
+            vec![GroupRef {
+                key: "orphan",
+                name: "Orphan"
+            }]
+        );
+        assert_eq!(groups.group_path("loop").len(), 1);
+        assert!(groups.group_path("missing").is_empty());
+    }
+
+    #[test]
+    fn test_partial_descriptions() {
+        let web_features = WebFeatures::from_json_str(
+            &json!({
+                "features": {
+                    "no-description": {
+                        "name": "No description",
+                        "description_html": "The <code>&lt;dialog&gt;</code> element",
+                    },
+                    "no-description-html": {
+                        "name": "No description html",
+                        "description": "The <dialog> element",
+                    },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let feature = &web_features.features["no-description"];
+        assert_eq!(feature.description(), "The <dialog> element");
+        assert_eq!(
+            feature.description_html(),
+            "The <code>&lt;dialog&gt;</code> element"
+        );
+        let feature = &web_features.features["no-description-html"];
+        assert_eq!(feature.description(), "The <dialog> element");
+        assert_eq!(feature.description_html(), "The &lt;dialog&gt; element");
+    }
+
+    #[test]
+    fn test_duplicate_compat_keys() {
+        let web_features = WebFeatures::from_json_str(
+            &json!({
+                "features": {
+                    "old-grid": {
+                        "name": "Old grid",
+                        "compat_features": ["css.properties.grid", "css.properties.grid-gap"],
+                        "discouraged": { "according_to": ["https://example.com"] },
+                    },
+                    "grid": {
+                        "name": "Grid",
+                        "compat_features": ["css.properties.grid", "css.properties.grid-area"],
+                    },
+                    "zz-grid": {
+                        "name": "ZZ grid",
+                        "compat_features": ["css.properties.grid"],
+                    },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            web_features.duplicate_compat_keys(),
+            vec![(
+                "css.properties.grid".to_string(),
+                vec![
+                    "old-grid".to_string(),
+                    "grid".to_string(),
+                    "zz-grid".to_string()
+                ]
+            )]
+        );
+        let ks = web_features
+            .bcd_keys
+            .iter()
+            .find(|ks| ks.bcd_key == "css properties grid")
+            .unwrap();
+        assert_eq!(ks.feature, "grid");
+    }
+
+    #[test]
+    fn test_features_for_spec() {
+        let features = IndexMap::from([
+            (
+                "grid".to_string(),
+                feature(json!({
+                    "name": "Grid",
+                    "description": "Grid",
+                    "description_html": "Grid",
+                    "spec": "https://drafts.csswg.org/css-grid-1/",
+                })),
+            ),
+            (
+                "subgrid".to_string(),
+                feature(json!({
+                    "name": "Subgrid",
+                    "description": "Subgrid",
+                    "description_html": "Subgrid",
+                    "spec": [
+                        "https://drafts.csswg.org/css-grid-1/#subgrids",
+                        "https://drafts.csswg.org/css-grid-2/",
+                    ],
+                })),
+            ),
+        ]);
+        let web_features = WebFeatures::from_features(features);
+        let spec = Url::parse("https://drafts.csswg.org/css-grid-1").unwrap();
+        assert_eq!(
+            web_features.features_for_spec(&spec),
+            vec!["grid", "subgrid"]
+        );
+        let spec = Url::parse("https://drafts.csswg.org/css-grid-2/#foo").unwrap();
+        assert_eq!(web_features.features_for_spec(&spec), vec!["subgrid"]);
+        let spec = Url::parse("https://drafts.csswg.org/css-grid-3/").unwrap();
+        assert!(web_features.features_for_spec(&spec).is_empty());
+    }
+
+    #[test]
+    fn test_iter() {
+        let features = IndexMap::from(["zoom", "anchor", "grid"].map(|k| {
+            (
+                k.to_string(),
+                feature(json!({
+                    "name": k,
+                    "description": k,
+                    "description_html": k,
+                })),
+            )
+        }));
+        let web_features = WebFeatures::from_features(features);
+        assert_eq!(
+            web_features.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            ["zoom", "anchor", "grid"]
+        );
+        assert_eq!(web_features.len(), 3);
+        assert_eq!(
+            web_features.get("anchor").map(|fd| fd.name.as_str()),
+            Some("anchor")
+        );
+        assert!(web_features.get("flexbox").is_none());
+    }
+
+    #[test]
+    fn test_suspicious_spec_urls() {
+        let features = IndexMap::from([
+            (
+                "grid".to_string(),
+                feature(json!({
+                    "name": "Grid",
+                    "description": "Grid",
+                    "description_html": "Grid",
+                    "spec": [
+                        "https://drafts.csswg.org/css-grid-1/",
+                        "https://drafts.cswg.org/css-grid-2/",
+                    ],
+                })),
+            ),
+            (
+                "fetch".to_string(),
+                feature(json!({
+                    "name": "Fetch",
+                    "description": "Fetch",
+                    "description_html": "Fetch",
+                    "spec": [
+                        "https://fetch.spec.whatwg.org/",
+                        "https://notw3.org/fetch/",
+                    ],
+                })),
+            ),
+        ]);
+        let web_features = WebFeatures::from_features(features);
+        let suspicious = web_features
+            .suspicious_spec_urls()
+            .into_iter()
+            .map(|(feature, url)| (feature, url.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            suspicious,
+            vec![
+                ("grid".to_string(), "https://drafts.cswg.org/css-grid-2/"),
+                ("fetch".to_string(), "https://notw3.org/fetch/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_features_in_snapshot() {
+        let features = IndexMap::from([
+            (
+                "nullish-coalescing".to_string(),
+                feature(json!({
+                    "name": "Nullish coalescing",
+                    "description": "Nullish coalescing",
+                    "description_html": "Nullish coalescing",
+                    "snapshot": "ecmascript-2020",
+                })),
+            ),
+            (
+                "optional-chaining".to_string(),
+                feature(json!({
+                    "name": "Optional chaining",
+                    "description": "Optional chaining",
+                    "description_html": "Optional chaining",
+                    "snapshot": ["ecmascript-2020"],
+                })),
+            ),
+        ]);
+        let web_features = WebFeatures::from_features(features);
+        assert_eq!(
+            web_features.features_in_snapshot("ecmascript-2020"),
+            vec!["nullish-coalescing", "optional-chaining"]
+        );
+        assert!(web_features
+            .features_in_snapshot("ecmascript-2021")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_builds_indexes() {
+        let features = IndexMap::from([(
+            "nullish-coalescing".to_string(),
+            feature(json!({
+                "name": "Nullish coalescing",
+                "snapshot": "ecmascript-2020",
+                "compat_features": ["javascript.operators.nullish_coalescing"],
+            })),
+        )]);
+        let json = serde_json::to_string(&WebFeatures::from_features(features)).unwrap();
+        let web_features: WebFeatures = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            web_features.features_in_snapshot("ecmascript-2020"),
+            vec!["nullish-coalescing"]
+        );
+        assert_eq!(
+            web_features.all_compat_keys().collect::<Vec<_>>(),
+            vec!["javascript.operators.nullish_coalescing"]
+        );
+    }
+
+    #[test]
+    fn test_all_compat_keys() {
+        let features = IndexMap::from([
+            (
+                "csp".to_string(),
+                feature(json!({
+                    "name": "CSP",
+                    "compat_features": [
+                        "http.headers.Content-Security-Policy-Report-Only",
+                        "http.headers.Content-Security-Policy",
+                        "http.headers.Content-Security-Policy.base-uri",
+                    ],
+                })),
+            ),
+            (
+                "csp-base-uri".to_string(),
+                feature(json!({
+                    "name": "CSP base-uri",
+                    "compat_features": ["http.headers.Content-Security-Policy.base-uri"],
+                })),
+            ),
+        ]);
+        let web_features = WebFeatures::from_features(features);
+        assert_eq!(
+            web_features.all_compat_keys().collect::<Vec<_>>(),
+            vec![
+                "http.headers.Content-Security-Policy",
+                "http.headers.Content-Security-Policy.base-uri",
+                "http.headers.Content-Security-Policy-Report-Only",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unmatched_keys() {
+        let features = IndexMap::from([(
+            "grid".to_string(),
+            feature(json!({
+                "name": "Grid",
+                "description": "Grid",
+                "description_html": "Grid",
+                "compat_features": ["css.properties.grid", "css.properties.grid-area"],
+            })),
+        )]);
+        let web_features = WebFeatures::from_features(features);
+        assert_eq!(
+            web_features.feature_for_compat_key("css.properties.grid-area"),
+            Some("grid")
+        );
+        assert_eq!(
+            web_features.feature_for_compat_key("css.properties.grid-gap"),
+            None
+        );
+        assert_eq!(
+            web_features.unmatched_keys(&[
+                "css.properties.grid",
+                "css.properties.grid-gap",
+                "css.properties.grid-area",
+                "css.properties",
+            ]),
+            vec!["css.properties.grid-gap", "css.properties"]
+        );
+    }
+
+    #[test]
+    fn test_baseline_year() {
+        let status =
+            |value: Value| -> SupportStatusWithByKey { serde_json::from_value(value).unwrap() };
+        assert_eq!(
+            status(json!({
+                "baseline": "high",
+                "baseline_low_date": "2020-01-15",
+                "baseline_high_date": "2022-07-15",
+                "support": {},
+            }))
+            .baseline_year(),
+            Some(2022)
+        );
+        assert_eq!(
+            status(json!({
+                "baseline": "low",
+                "baseline_low_date": "≤2023-03-27",
+                "support": {},
+            }))
+            .baseline_year(),
+            Some(2023)
+        );
+        assert_eq!(
+            status(json!({ "baseline": false, "support": {} })).baseline_year(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_support_versions() {
+        let support: Support = serde_json::from_value(json!({
+            "chrome": "120",
+            "firefox": "preview",
+            "safari": "≤14",
+            "safari_ios": "15.4",
+        }))
+        .unwrap();
+        assert_eq!(
+            support.chrome,
+            Some(SupportVersion::Exact("120".to_string()))
+        );
+        assert_eq!(support.firefox, Some(SupportVersion::Preview));
+        assert_eq!(
+            support.safari,
+            Some(SupportVersion::OrEarlier("14".to_string()))
+        );
+        assert_eq!(
+            support.safari_ios,
+            Some(SupportVersion::Exact("15.4".to_string()))
+        );
+        assert_eq!(support.edge, None);
+        assert_eq!(
+            serde_json::to_value(&support).unwrap(),
+            json!({
+                "chrome": "120",
+                "firefox": "preview",
+                "safari": "≤14",
+                "safari_ios": "15.4",
+            })
+        );
+    }
+
+    #[test]
+    fn test_support_summary() {
+        let support: Support = serde_json::from_value(json!({
+            "chrome": "111",
+            "chrome_android": "111",
+            "firefox": "preview",
+            "safari": "≤16.4",
+            "safari_ios": "16.4",
+            "servo": "0.1",
+        }))
+        .unwrap();
+        assert_eq!(
+            support.summary(),
+            "Chrome 111, Firefox preview, Safari ≤16.4, Chrome Android 111, Safari on iOS 16.4, servo 0.1"
+        );
+        let support: Support = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(support.summary(), "");
+    }
+
+    #[test]
+    fn test_missing_support() {
+        let web_features = WebFeatures::from_json_str(
+            &json!({
+                "features": {
+                    "grid": {
+                        "name": "Grid",
+                        "compat_features": ["css.properties.grid"],
+                        "status": {
+                            "baseline": "low",
+                            "baseline_low_date": "2023-03-27",
+                            "by_compat_key": {
+                                "css.properties.grid": {
+                                    "baseline": "low",
+                                    "baseline_low_date": "2023-03-27",
+                                },
+                            },
+                        },
+                    },
+                    "subgrid": {
+                        "name": "Subgrid",
+                        "compat_features": ["css.properties.grid-template-columns.subgrid"],
+                        "status": {
+                            "baseline": false,
+                            "support": {},
+                            "by_compat_key": {
+                                "css.properties.grid-template-columns.subgrid": {
+                                    "baseline": false,
+                                },
+                            },
+                        },
+                    },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let grid = web_features.feature_status("css.properties.grid").unwrap();
+        assert_eq!(grid.support.baseline, Some(BaselineHighLow::Low));
+        assert!(grid.support.support.is_empty());
+        assert_eq!(grid.support.support_summary(), "");
+        let subgrid = web_features
+            .feature_status("css.properties.grid-template-columns.subgrid")
+            .unwrap();
+        assert_eq!(
+            subgrid.support.baseline,
+            Some(BaselineHighLow::False(false))
+        );
+        assert_eq!(subgrid.support.support.get(BrowserIdentifier::Chrome), None);
+    }
+
+    #[test]
+    fn test_unknown_browser() {
+        let web_features = WebFeatures::from_json_str(
+            &json!({
+                "features": {
+                    "grid": {
+                        "name": "Grid",
+                        "status": {
+                            "baseline": "high",
+                            "support": { "chrome": "57", "servo": "0.1" },
+                        },
+                    },
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let support = &web_features.features["grid"]
+            .status
+            .as_ref()
+            .unwrap()
+            .support;
+        assert_eq!(
+            support.chrome,
+            Some(SupportVersion::Exact("57".to_string()))
+        );
+        assert_eq!(
+            support.other.get("servo"),
+            Some(&SupportVersion::Exact("0.1".to_string()))
+        );
+        assert_eq!(
+            serde_json::to_value(support).unwrap(),
+            json!({ "chrome": "57", "servo": "0.1" })
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let dataset = |grid: Value, subgrid: Value, nesting: Value| {
+            WebFeatures::from_json_str(
+                &json!({
+                    "features": {
+                        "grid": {
+                            "name": "Grid",
+                            "status": { "baseline": grid, "support": {} },
+                        },
+                        "subgrid": {
+                            "name": "Subgrid",
+                            "status": { "baseline": subgrid, "support": {} },
+                        },
+                        "nesting": {
+                            "name": "Nesting",
+                            "status": { "baseline": nesting, "support": {} },
+                        },
+                    }
+                })
+                .to_string(),
+            )
+            .unwrap()
+        };
+        let older = dataset(json!("low"), json!(false), json!("high"));
+        let newer = dataset(json!("high"), json!("low"), json!("low"));
+        let changes = newer.diff(&older);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (change.feature.as_str(), change.kind()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("grid", BaselineChangeKind::Promoted),
+                ("subgrid", BaselineChangeKind::NewlyBaseline),
+                ("nesting", BaselineChangeKind::Regressed),
+            ]
+        );
+        assert_eq!(changes[0].old, BaselineHighLow::Low);
+        assert_eq!(changes[0].new, BaselineHighLow::High);
+        assert!(newer.diff(&newer).is_empty());
+    }
+
+    #[test]
+    fn test_baseline_high_low_order() {
+        let mut statuses = vec![
+            BaselineHighLow::High,
+            BaselineHighLow::False(true),
+            BaselineHighLow::Low,
+            BaselineHighLow::False(false),
+            BaselineHighLow::High,
+        ];
+        statuses.sort();
+        assert_eq!(
+            statuses,
+            vec![
+                BaselineHighLow::False(false),
+                BaselineHighLow::False(true),
+                BaselineHighLow::Low,
+                BaselineHighLow::High,
+                BaselineHighLow::High,
+            ]
+        );
+        assert!(BaselineHighLow::Low >= BaselineHighLow::Low);
+        assert!(BaselineHighLow::High > BaselineHighLow::Low);
+        assert!(BaselineHighLow::False(true) < BaselineHighLow::Low);
+    }
+
+    #[test]
+    fn test_baseline_high_low_rank() {
+        assert_eq!(BaselineHighLow::False(false).rank(), 0);
+        assert_eq!(BaselineHighLow::False(true).rank(), 0);
+        assert_eq!(BaselineHighLow::Low.rank(), 1);
+        assert_eq!(BaselineHighLow::High.rank(), 2);
+        assert!(!BaselineHighLow::False(false).is_baseline());
+        assert!(!BaselineHighLow::False(true).is_baseline());
+        assert!(BaselineHighLow::Low.is_baseline());
+        assert!(BaselineHighLow::High.is_baseline());
+    }
+}
@@ -3,28 +3,53 @@
 //! The `baseline` module provides functionality for managing and accessing baseline support status
 //! for web features. It includes utilities for loading baseline data from files and retrieving
 //! support status for specific browser compatibility keys.
-use std::sync::LazyLock;
+use std::sync::OnceLock;
 
 use rari_data::baseline::{Baseline, WebFeatures};
 use rari_types::globals::data_dir;
+use serde::Serialize;
 use tracing::warn;
 
-static WEB_FEATURES: LazyLock<Option<WebFeatures>> = LazyLock::new(|| {
-    let web_features =
-        WebFeatures::from_file(&data_dir().join("baseline").join("data.extended.json"));
-    match web_features {
-        Ok(web_features) => Some(web_features),
-        Err(e) => {
-            warn!("{e:?}");
-            None
-        }
-    }
-});
+static WEB_FEATURES: OnceLock<Option<WebFeatures>> = OnceLock::new();
+
+/// Provides access to the process-wide `WebFeatures`.
+///
+/// The baseline data is read from `<data_dir>/baseline/data.extended.json` and indexed on first
+/// access. Subsequent calls return the cached value. If loading fails the error is logged once and
+/// `None` is returned from then on.
+///
+/// # Returns
+///
+/// * `Option<&'static WebFeatures>` - Returns the loaded `WebFeatures`, or `None` if they could not be loaded.
+pub fn web_features() -> Option<&'static WebFeatures> {
+    WEB_FEATURES
+        .get_or_init(|| {
+            match WebFeatures::from_file(&data_dir().join("baseline").join("data.extended.json")) {
+                Ok(web_features) => Some(web_features),
+                Err(e) => {
+                    warn!("{e:?}");
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Overrides the process-wide `WebFeatures`, e.g. to inject a fixture in tests.
+///
+/// This must be called before the first call to [`web_features`].
+///
+/// # Returns
+///
+/// * `bool` - Returns `false`, dropping the passed-in value, if `WebFeatures` were already initialized.
+pub fn set_web_features(web_features: WebFeatures) -> bool {
+    WEB_FEATURES.set(Some(web_features)).is_ok()
+}
 
 /// Retrieves the baseline support status for a given browser compatibility key.
 ///
 /// This function looks up the baseline support status for the provided browser compatibility key
-/// in the `WEB_FEATURES` static variable. If it contains the specified key, it returns the corresponding
+/// in the cached [`web_features`]. If it contains the specified key, it returns the corresponding
 /// `SupportStatusWithByKey`. If the key is not found, it returns `None`.
 ///
 /// # Arguments
@@ -35,9 +60,9 @@ static WEB_FEATURES: LazyLock<Option<WebFeatures>> = LazyLock::new(|| {
 /// # Returns
 ///
 /// * `Option<&'static SupportStatusWithByKey>` - Returns `Some(&SupportStatusWithByKey)` if the key is found,
-///   or `None` if the key is not found or `WEB_FEATURES` is not initialized.
+///   or `None` if the key is not found or the `WebFeatures` could not be loaded.
 pub(crate) fn get_baseline<'a>(browser_compat: &[String]) -> Option<Baseline<'a>> {
-    if let Some(ref web_features) = *WEB_FEATURES {
+    if let Some(web_features) = web_features() {
         return match &browser_compat {
             &[bcd_key] => web_features.feature_status(bcd_key.as_str()),
             _ => None,
@@ -45,3 +70,51 @@ pub(crate) fn get_baseline<'a>(browser_compat: &[String]) -> Option<Baseline<'a>
     }
     None
 }
+
+/// The Baseline status of a single BCD key, as returned by [`baseline_status`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum BaselineStatus {
+    /// The key's Baseline status with dates and browser support.
+    Found(Baseline<'static>),
+    /// The key belongs to `feature`, which is discouraged and has no status shown.
+    Discouraged { feature: &'static str },
+    /// The key belongs to `feature`, but its status differs from the feature's.
+    NoStatus { feature: &'static str },
+    /// No feature lists the key in its `compat_features`.
+    NotFound,
+    /// The `WebFeatures` could not be loaded.
+    Unavailable,
+}
+
+/// Looks up the Baseline status of `bcd_key` in the cached [`web_features`].
+///
+/// Unlike [`get_baseline`] this tells apart why there is no status, for commands and
+/// endpoints reporting on a single key.
+///
+/// # Arguments
+///
+/// * `bcd_key` - A string slice that holds the browser compatibility key, e.g. `css.properties.grid`.
+///
+/// # Returns
+///
+/// * `BaselineStatus` - The status if found, or the reason there is none.
+pub fn baseline_status(bcd_key: &str) -> BaselineStatus {
+    let Some(web_features) = web_features() else {
+        return BaselineStatus::Unavailable;
+    };
+    if let Some(baseline) = web_features.feature_status(bcd_key) {
+        return BaselineStatus::Found(baseline);
+    }
+    match web_features.feature_for_compat_key(bcd_key) {
+        Some(feature)
+            if web_features
+                .get(feature)
+                .is_some_and(|fd| fd.discouraged.is_some()) =>
+        {
+            BaselineStatus::Discouraged { feature }
+        }
+        Some(feature) => BaselineStatus::NoStatus { feature },
+        None => BaselineStatus::NotFound,
+    }
+}
@@ -16,10 +16,15 @@
 //!   - `CACHED_DOC_PAGE_FILES`: Stores documentation pages indexed by file path.
 //!   - `CACHED_SIDEBAR_FILES`: Stores sidebar metadata indexed by name and locale.
 //!
+//!   While `refresh_cache()` is set, these are re-read and the cached entries replaced.
+//!
 //! - **Specialized Caches**: These caches store specific types of documentation content.
 //!   - `CACHED_CURRICULUM`: Stores curriculum files, indexed by URL, path, and index,
 //!   - `GENERIC_CONTENT_FILES`: Stores generic pages indexed by URL.
 //!   - `CONTRIBUTOR_SPOTLIGHT_FILES`: Stores contributor spotlight pages indexed by URL.
+//!
+//!   These are initialized once, so while `refresh_cache()` is set they are read fresh
+//!   without touching the cached value.
 
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -30,7 +35,7 @@ use std::sync::{Arc, LazyLock, OnceLock};
 use dashmap::DashMap;
 use rari_types::globals::{
     blog_root, cache_content, content_root, content_translated_root, contributor_spotlight_root,
-    curriculum_root, generic_content_root,
+    curriculum_root, generic_content_root, refresh_cache,
 };
 use rari_types::locale::Locale;
 use rari_utils::concat_strs;
@@ -139,7 +144,7 @@ pub fn read_sidebar(name: &str, locale: Locale, slug: &str) -> Result<Arc<MetaSi
         "jsref" => Arc::new(jsref::sidebar(slug, locale)?),
         _ => {
             let key = (name.to_string(), locale);
-            if cache_content() {
+            if cache_content() && !refresh_cache() {
                 if let Some(sidebar) = CACHED_SIDEBAR_FILES.get(&key) {
                     return Ok(sidebar.clone());
                 }
@@ -358,7 +363,7 @@ fn gather_contributor_spotlight() -> Result<HashMap<String, Page>, DocError> {
 /// * `Cow<'static, CurriculumFiles>` - Returns a `Cow::Borrowed` containing the cached curriculum files
 ///   if caching is enabled. Otherwise, returns a `Cow::Owned` containing the gathered curriculum files.
 pub fn curriculum_files() -> Cow<'static, CurriculumFiles> {
-    if cache_content() {
+    if cache_content() && !refresh_cache() {
         Cow::Borrowed(CACHED_CURRICULUM.get_or_init(|| {
             gather_curriculum()
                 .inspect_err(|e| warn!("{e}"))
@@ -441,7 +446,7 @@ pub fn blog_files() -> Cow<'static, BlogFiles> {
             sorted_meta,
         }
     }
-    if cache_content() {
+    if cache_content() && !refresh_cache() {
         Cow::Borrowed(BLOG_FILES.get_or_init(gather))
     } else {
         Cow::Owned(gather())
@@ -586,7 +591,7 @@ pub fn generic_content_config() -> Cow<'static, GenericContentConfig> {
             Default::default()
         })
     }
-    if cache_content() {
+    if cache_content() && !refresh_cache() {
         Cow::Borrowed(GENERIC_CONTENT_CONFIG.get_or_init(gather))
     } else {
         Cow::Owned(gather())
@@ -611,7 +616,7 @@ pub fn generic_content_files() -> Cow<'static, UrlToPageMap> {
             Default::default()
         })
     }
-    if cache_content() {
+    if cache_content() && !refresh_cache() {
         Cow::Borrowed(GENERIC_CONTENT_FILES.get_or_init(gather))
     } else {
         Cow::Owned(gather())
@@ -636,7 +641,7 @@ pub fn contributor_spotlight_files() -> Cow<'static, UrlToPageMap> {
             Default::default()
         })
     }
-    if cache_content() {
+    if cache_content() && !refresh_cache() {
         Cow::Borrowed(CONTRIBUTOR_SPOTLIGHT_FILES.get_or_init(gather))
     } else {
         Cow::Owned(gather())
@@ -732,7 +737,7 @@ pub fn wiki_histories() -> Cow<'static, WikiHistories> {
         map.insert(Locale::EnUs, history);
         Ok(map)
     }
-    if cache_content() {
+    if cache_content() && !refresh_cache() {
         Cow::Borrowed(WIKI_HISTORY.get_or_init(|| {
             gather()
                 .inspect_err(|e| tracing::error!("Error reading wiki histories: {e}"))
@@ -121,8 +121,15 @@ pub enum DocError {
 /// Represents various errors that can occur while processing URLs.
 #[derive(Debug, Error)]
 pub enum UrlError {
-    #[error("invalid url")]
-    InvalidUrl,
+    /// The locale segment isn't a supported locale.
+    #[error("unknown locale: {0}")]
+    UnknownLocale(String),
+    /// The path after the locale isn't a known page category, SPA or generic page.
+    #[error("unknown page category: {0}")]
+    UnknownCategory(String),
+    /// The url isn't an absolute path.
+    #[error("malformed url: {0}")]
+    MalformedUrl(String),
     #[error(transparent)]
     LocaleError(#[from] LocaleError),
     #[error(transparent)]
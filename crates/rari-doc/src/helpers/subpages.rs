@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use memoize::memoize;
 use rari_types::fm_types::PageType;
-use rari_types::globals::{cache_content, deny_warnings};
+use rari_types::globals::{cache_content, deny_warnings, refresh_cache};
 use rari_types::locale::Locale;
 
 use super::l10n::l10n_json_data;
@@ -83,6 +83,9 @@ pub fn write_li_with_badges(
             badge_locale: locale,
             code,
             only_en_us: locale_page.locale() != locale,
+            rari_link: None,
+            base_url: None,
+            raw_anchor: false,
         },
         true,
     )?;
@@ -106,6 +109,9 @@ pub fn write_parent_li(out: &mut String, page: &Page, locale: Locale) -> Result<
             badge_locale: locale,
             code: false,
             only_en_us: page.locale() != locale,
+            rari_link: None,
+            base_url: None,
+            raw_anchor: false,
         },
         true,
     )?;
@@ -316,7 +322,7 @@ pub fn get_sub_pages(
 }
 
 fn read_sub_folders(folder: PathBuf, depth: Option<usize>) -> Result<Vec<PathBuf>, ignore::Error> {
-    if cache_content() {
+    if cache_content() && !refresh_cache() {
         read_sub_folders_internal(folder, depth)
     } else {
         memoized_original_read_sub_folders_internal(folder, depth)
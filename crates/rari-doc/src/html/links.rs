@@ -1,22 +1,79 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::mpsc::Sender;
+use std::sync::OnceLock;
 
 use rari_md::anchor::anchorize;
 use rari_types::fm_types::FeatureStatus;
 use rari_types::locale::Locale;
 use rari_utils::concat_strs;
+use rari_utils::html::html_to_text;
+use serde::Serialize;
+use tracing::error;
 
 use crate::error::DocError;
+use crate::helpers::l10n::l10n_json_data;
+use crate::helpers::summary_hack::get_hacky_summary_md;
 use crate::issues::get_issue_counter;
-use crate::pages::page::{Page, PageLike};
-use crate::resolve::locale_from_url;
+use crate::pages::page::{Page, PageCategory, PageLike};
+use crate::resolve::strip_locale_from_url;
 use crate::templ::api::RariApi;
 use crate::templ::templs::badges::{write_deprecated, write_experimental, write_non_standard};
+use crate::utils::dedup_whitespace;
 
 pub struct LinkModifier<'a> {
     pub badges: &'a [FeatureStatus],
     pub badge_locale: Locale,
     pub code: bool,
     pub only_en_us: bool,
+    /// Mark the link with a `data-rari-link` attribute for later processing.
+    pub rari_link: Option<RariLink>,
+    /// Origin to prefix internal hrefs with, e.g. `https://developer.mozilla.org` for feeds.
+    pub base_url: Option<&'a str>,
+    /// The anchor is already a valid id (e.g. from the TOC), don't run it through `anchorize`.
+    pub raw_anchor: bool,
+}
+
+/// Where the badges of a link to a resolved page come from.
+#[derive(Clone, Copy, Debug)]
+enum Badges<'a> {
+    None,
+    /// The target page's own status.
+    Page,
+    /// Fixed badges, regardless of the target page's status.
+    Explicit(&'a [FeatureStatus]),
+}
+
+impl<'a> Badges<'a> {
+    fn from_page(with_badges: bool) -> Self {
+        if with_badges {
+            Badges::Page
+        } else {
+            Badges::None
+        }
+    }
+
+    fn for_page<'b>(self, page: &'b Page) -> &'b [FeatureStatus]
+    where
+        'a: 'b,
+    {
+        match self {
+            Badges::None => &[],
+            Badges::Page => page.status(),
+            Badges::Explicit(badges) => badges,
+        }
+    }
+}
+
+/// How a link generated by rari is marked via `data-rari-link`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RariLink {
+    /// Emit a bare `data-rari-link` attribute.
+    Plain,
+    /// Emit `data-rari-link="<category>"` with the resolved `PageCategory`.
+    Category(PageCategory),
 }
 
 pub fn render_internal_link(
@@ -28,11 +85,27 @@ pub fn render_internal_link(
     modifier: &LinkModifier,
     checked: bool,
 ) -> Result<(), DocError> {
+    let content = if content.trim().is_empty() {
+        warn_empty_content(url);
+        // Fall back to last url path segment.
+        url.trim_end_matches('/').rsplit('/').next().unwrap_or(url)
+    } else {
+        content
+    };
     out.push_str("<a href=\"");
+    if let Some(base_url) = modifier.base_url {
+        if url.starts_with('/') && !url.starts_with("//") {
+            out.push_str(base_url.strip_suffix('/').unwrap_or(base_url));
+        }
+    }
     out.push_str(url);
     if let Some(anchor) = anchor {
         out.push('#');
-        out.push_str(&anchorize(anchor));
+        if modifier.raw_anchor {
+            out.push_str(anchor);
+        } else {
+            out.push_str(&anchorize(anchor));
+        }
     }
     out.push('"');
     if let Some(title) = title {
@@ -43,11 +116,18 @@ pub fn render_internal_link(
         ]);
     }
     if modifier.only_en_us {
-        out.push_str(" class=\"only-in-en-us\"");
+        out.push_str(" class=\"only-in-en-us\" hreflang=\"en-US\"");
     }
     if checked {
         out.push_str(" data-templ-link");
     }
+    match modifier.rari_link {
+        Some(RariLink::Plain) => out.push_str(" data-rari-link"),
+        Some(RariLink::Category(category)) => {
+            out.extend([" data-rari-link=\"", category.as_str(), "\""])
+        }
+        None => {}
+    }
     out.push('>');
     if modifier.code {
         out.push_str("<code>");
@@ -76,15 +156,107 @@ pub fn render_link_from_page(
     page: &Page,
     modifier: &LinkModifier,
 ) -> Result<(), DocError> {
-    let content = page.short_title().unwrap_or(page.title());
-    let decoded_content = html_escape::decode_html_entities(content);
-    let encoded_content = html_escape::encode_safe(&decoded_content);
-    let content = if content != encoded_content {
-        Cow::Owned(encoded_content.into_owned())
-    } else {
+    let content = safe_link_content(page.short_title().unwrap_or(page.title()));
+    if modifier.rari_link == Some(RariLink::Plain) {
+        // We know the target, so upgrade the mark with its category.
+        let modifier = LinkModifier {
+            rari_link: Some(RariLink::Category(page.page_category())),
+            ..*modifier
+        };
+        return render_internal_link(out, page.url(), None, &content, None, &modifier, true);
+    }
+    render_internal_link(out, page.url(), None, &content, None, modifier, true)
+}
+
+/// An empty link text makes an inaccessible link, so blank `content` is dropped (with a
+/// warning) for the callers to fall back to their default text.
+fn non_blank_content<'a>(content: Option<&'a str>, url: &str) -> Option<&'a str> {
+    match content {
+        Some(content) if content.trim().is_empty() => {
+            warn_empty_content(url);
+            None
+        }
+        content => content,
+    }
+}
+
+fn warn_empty_content(url: &str) {
+    let ic = get_issue_counter();
+    tracing::warn!(source = "empty-link-content", ic = ic, url = url);
+}
+
+/// Renders a link to `page` exactly like [`render_link_via_page`] would when resolving to it,
+/// without looking the page up again.
+///
+/// The link text is `content`, or the page's (short) title if `None`. `anchor` is anchorized.
+#[allow(clippy::too_many_arguments)]
+pub fn render_link_from_page_full(
+    out: &mut String,
+    page: &Page,
+    anchor: Option<&str>,
+    content: Option<&str>,
+    code: bool,
+    title: Option<&str>,
+    locale: Locale,
+    with_badges: bool,
+) -> Result<(), DocError> {
+    render_link_to_page(
+        out,
+        page,
+        &LinkSpec {
+            link: page.url(),
+            content: non_blank_content(content, page.url()),
+            code,
+            title,
+        },
+        anchor,
+        locale,
+        Badges::from_page(with_badges),
+    )
+}
+
+/// Renders a link to the resolved `page`, with `spec.link` as the href (the page url plus
+/// any query).
+fn render_link_to_page(
+    out: &mut String,
+    page: &Page,
+    spec: &LinkSpec,
+    anchor: Option<&str>,
+    locale: Locale,
+    badges: Badges,
+) -> Result<(), DocError> {
+    record_link(spec.link, true, true);
+    let content = if let Some(content) = spec.content {
         Cow::Borrowed(content)
+    } else {
+        safe_link_content(page.short_title().unwrap_or(page.title()))
     };
-    render_internal_link(out, page.url(), None, &content, None, modifier, true)
+    render_internal_link(
+        out,
+        spec.link,
+        anchor,
+        &content,
+        spec.title,
+        &page_link_modifier(page, badges, spec.code, locale),
+        true,
+    )
+}
+
+fn page_link_modifier<'a>(
+    page: &'a Page,
+    badges: Badges<'a>,
+    code: bool,
+    locale: Locale,
+) -> LinkModifier<'a> {
+    LinkModifier {
+        badges: badges.for_page(page),
+        badge_locale: locale,
+        code,
+        only_en_us: page.locale() == Locale::EnUs && locale != Locale::EnUs,
+        rari_link: None,
+        base_url: None,
+        raw_anchor: false,
+    }
 }
 
 pub fn render_link_via_page(
@@ -96,13 +268,234 @@ pub fn render_link_via_page(
     title: Option<&str>,
     with_badges: bool,
 ) -> Result<(), DocError> {
+    render_link_via_page_with(
+        out,
+        &LinkSpec {
+            link,
+            content,
+            code,
+            title,
+        },
+        locale,
+        Badges::from_page(with_badges),
+        &mut |url| RariApi::get_page(url).ok(),
+    )?;
+    Ok(())
+}
+
+/// Like [`render_link_via_page`], but with `badges` instead of the target page's own status,
+/// e.g. when a macro knows the feature status from BCD.
+pub fn render_link_via_page_with_badges(
+    out: &mut String,
+    link: &str,
+    locale: Locale,
+    content: Option<&str>,
+    code: bool,
+    title: Option<&str>,
+    badges: &[FeatureStatus],
+) -> Result<(), DocError> {
+    render_link_via_page_with(
+        out,
+        &LinkSpec {
+            link,
+            content,
+            code,
+            title,
+        },
+        locale,
+        Badges::Explicit(badges),
+        &mut |url| RariApi::get_page(url).ok(),
+    )?;
+    Ok(())
+}
+
+/// Renders a link to the glossary entry for `term`, using the entry's summary as `title`.
+///
+/// The link text is `content`, or `term` if `None`. With `abbr` the link is wrapped in `<abbr>`.
+/// Missing glossary entries render like other missing pages, without a title.
+pub fn render_glossary_link(
+    out: &mut String,
+    term: &str,
+    locale: Locale,
+    content: Option<&str>,
+    abbr: bool,
+) -> Result<(), DocError> {
+    let url = concat_strs!(
+        "/",
+        locale.as_url_str(),
+        "/docs/Glossary/",
+        &dedup_whitespace(term).replace(' ', "_")
+    );
+    let page = RariApi::get_page(&url).ok();
+    let title = page
+        .as_ref()
+        .map(get_hacky_summary_md)
+        .transpose()?
+        .map(|summary| summary_to_title(&summary));
+    if abbr {
+        out.push_str("<abbr>");
+    }
+    render_link_via_page_with(
+        out,
+        &LinkSpec {
+            link: &url,
+            content: Some(content.unwrap_or(term)),
+            code: false,
+            title: title.as_deref(),
+        },
+        locale,
+        Badges::None,
+        &mut |_| page.clone(),
+    )?;
+    if abbr {
+        out.push_str("</abbr>");
+    }
+    Ok(())
+}
+
+/// Turns a rendered summary into plain text for a `title` attribute.
+///
+/// Tags are dropped and entities decoded, as the title gets attribute-encoded when rendered.
+fn summary_to_title(summary: &str) -> String {
+    html_to_text(summary)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A link rendered via [`render_link_via_page`] or [`render_links_via_page`], for the link graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkRecord {
+    /// Url of the page being built.
+    pub source: String,
+    /// The resolved url for internal links, the link as given otherwise.
+    pub target: String,
+    pub is_internal: bool,
+    pub resolved: bool,
+}
+
+/// Receives a [`LinkRecord`] for every link rendered while building pages, if set.
+///
+/// `None` is never sent by rari, so it can be used to tell the receiver to stop.
+pub static LINK_RECORDER_SENDER: OnceLock<Sender<Option<LinkRecord>>> = OnceLock::new();
+
+thread_local! {
+    static LINK_SOURCE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Attributes links rendered on this thread to a page until dropped, see [`LINK_RECORDER_SENDER`].
+pub(crate) struct LinkSource(Option<String>);
+
+impl LinkSource {
+    /// Returns `None` unless links are recorded.
+    pub(crate) fn enter(url: &str) -> Option<Self> {
+        LINK_RECORDER_SENDER.get()?;
+        Some(LinkSource(
+            LINK_SOURCE.with(|source| source.replace(Some(url.to_string()))),
+        ))
+    }
+}
+
+impl Drop for LinkSource {
+    fn drop(&mut self) {
+        LINK_SOURCE.with(|source| *source.borrow_mut() = self.0.take());
+    }
+}
+
+fn record_link(target: &str, is_internal: bool, resolved: bool) {
+    let Some(tx) = LINK_RECORDER_SENDER.get() else {
+        return;
+    };
+    let source = LINK_SOURCE.with(|source| source.borrow().clone());
+    let record = LinkRecord {
+        source: source.unwrap_or_default(),
+        target: target.to_string(),
+        is_internal,
+        resolved,
+    };
+    if let Err(e) = tx.send(Some(record)) {
+        error!("link recorder: {e}");
+    }
+}
+
+/// A single link for [`render_links_via_page`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinkSpec<'a> {
+    pub link: &'a str,
+    pub content: Option<&'a str>,
+    pub code: bool,
+    pub title: Option<&'a str>,
+}
+
+/// Renders `links` in order, separated by `separator`, like [`render_link_via_page`].
+///
+/// Targets are resolved once per URL for the whole batch. Returns the internal links
+/// whose target couldn't be resolved.
+pub fn render_links_via_page<'a>(
+    out: &mut String,
+    links: &[LinkSpec<'a>],
+    separator: &str,
+    locale: Locale,
+    with_badges: bool,
+) -> Result<Vec<&'a str>, DocError> {
+    let mut pages: HashMap<String, Option<Page>> = HashMap::new();
+    let mut get_page = |url: &str| {
+        pages
+            .entry(url.to_string())
+            .or_insert_with(|| RariApi::get_page(url).ok())
+            .clone()
+    };
+    let mut unresolved = vec![];
+    for (i, spec) in links.iter().enumerate() {
+        if i > 0 {
+            out.push_str(separator);
+        }
+        if !render_link_via_page_with(
+            out,
+            spec,
+            locale,
+            Badges::from_page(with_badges),
+            &mut get_page,
+        )? {
+            unresolved.push(spec.link);
+        }
+    }
+    Ok(unresolved)
+}
+
+/// Whether the first segment of `path` (without the leading `/`) is a locale, in any
+/// casing, like `fr/docs/Web` or `EN-US/docs/Web`.
+fn has_locale_segment(path: &str) -> bool {
+    path.split_once('/')
+        .is_some_and(|(segment, _)| Locale::from_str(&segment.to_ascii_lowercase()).is_ok())
+}
+
+/// Renders a link, resolving internal targets via `get_page`.
+///
+/// Returns `false` if the link is internal and its target couldn't be resolved.
+fn render_link_via_page_with(
+    out: &mut String,
+    &LinkSpec {
+        link,
+        content,
+        code,
+        title,
+    }: &LinkSpec,
+    locale: Locale,
+    badges: Badges,
+    get_page: &mut dyn FnMut(&str) -> Option<Page>,
+) -> Result<bool, DocError> {
+    let content = non_blank_content(content, link);
     let mut url = Cow::Borrowed(link);
+    let mut resolved = true;
+    let mut page_not_created = false;
     if let Some(link) = link.strip_prefix('/') {
-        if locale_from_url(&url).is_none() {
+        if !has_locale_segment(link) {
             url = Cow::Owned(concat_strs!("/", locale.as_url_str(), "/docs/", link));
         }
         let (url, anchor) = url.split_once('#').unwrap_or((&url, ""));
-        if let Ok(page) = RariApi::get_page(url) {
+        let (url, query) = url.split_once('?').unwrap_or((url, ""));
+        if let Some(page) = get_page(url) {
             if url != page.url() && url.to_lowercase() == page.url().to_lowercase() {
                 let ic = get_issue_counter();
                 tracing::warn!(
@@ -112,60 +505,66 @@ pub fn render_link_via_page(
                     redirect = page.url()
                 );
             }
-            let url = page.url();
-            let content = if let Some(content) = content {
-                Cow::Borrowed(content)
+            let url = if query.is_empty() {
+                Cow::Borrowed(page.url())
             } else {
-                let content = page.short_title().unwrap_or(page.title());
-                let decoded_content = html_escape::decode_html_entities(content);
-                let encoded_content = html_escape::encode_safe(&decoded_content);
-                if content != encoded_content {
-                    Cow::Owned(encoded_content.into_owned())
-                } else {
-                    Cow::Borrowed(content)
-                }
+                Cow::Owned(concat_strs!(page.url(), "?", query))
             };
-            return render_internal_link(
+            return render_link_to_page(
                 out,
-                url,
-                if anchor.is_empty() {
-                    None
-                } else {
-                    Some(anchor)
-                },
-                &content,
-                title,
-                &LinkModifier {
-                    badges: if with_badges { page.status() } else { &[] },
-                    badge_locale: locale,
+                &page,
+                &LinkSpec {
+                    link: &url,
+                    content,
                     code,
-                    only_en_us: page.locale() == Locale::EnUs && locale != Locale::EnUs,
+                    title,
                 },
-                true,
-            );
+                (!anchor.is_empty()).then_some(anchor),
+                locale,
+                badges,
+            )
+            .map(|_| true);
         }
+        resolved = false;
+        // Missing docs pages get the same treatment as in `fix_link`.
+        let (url_locale, path) = strip_locale_from_url(url);
+        page_not_created =
+            url_locale.is_some() && path.starts_with("/docs/") && !Page::ignore_link_check(url);
     }
 
-    out.push_str("<a data-templ-link href=\"");
+    record_link(&url, link.starts_with('/'), resolved);
+    let scheme_and_address = split_non_http_scheme(&url);
+    out.push_str(if page_not_created {
+        "<a class=\"page-not-created\" data-href=\""
+    } else if scheme_and_address.is_some() {
+        "<a href=\""
+    } else {
+        "<a data-templ-link href=\""
+    });
     let content = match content {
-        Some(content) => {
-            let decoded_content = html_escape::decode_html_entities(content);
-            let encoded_content = html_escape::encode_safe(&decoded_content);
-            if content != encoded_content {
-                Cow::Owned(encoded_content.into_owned())
-            } else {
-                Cow::Borrowed(content)
-            }
-        }
+        Some(content) => safe_link_content(content),
         None if url.starts_with('/') => {
             // Fall back to last url path segment.
             let clean_url = url.strip_suffix("/").unwrap_or(&url);
             let content = &clean_url[clean_url.rfind('/').map(|i| i + 1).unwrap_or(0)..];
             Cow::Borrowed(content)
         }
-        _ => html_escape::encode_safe(&url),
+        None => match scheme_and_address {
+            // Fall back to the address or number for mailto: and tel: links.
+            Some((scheme, address)) if scheme.eq_ignore_ascii_case("mailto") => {
+                html_escape::encode_safe(&address[..address.find('?').unwrap_or(address.len())])
+            }
+            Some((scheme, number)) if scheme.eq_ignore_ascii_case("tel") => {
+                html_escape::encode_safe(number)
+            }
+            _ => html_escape::encode_safe(&url),
+        },
     };
     out.push_str(&url);
+    let title = match title {
+        None if page_not_created => Some(l10n_json_data("Common", "summary", locale)?),
+        title => title,
+    };
     if let Some(title) = title {
         out.push_str("\" title=\"");
         out.push_str(&html_escape::encode_quoted_attribute(title));
@@ -179,5 +578,525 @@ pub fn render_link_via_page(
         out.push_str("</code>");
     }
     out.push_str("</a>");
-    Ok(())
+    Ok(resolved)
+}
+
+/// Encodes text for use as link content, without double-encoding existing entities.
+///
+/// Entities are decoded first and the result is encoded again, so `&amp;` stays `&amp;`, a literal `&`
+/// becomes `&amp;` and `<`/`>` become `&lt;`/`&gt;`. Returns the input unchanged if it already is in
+/// that form.
+pub(crate) fn safe_link_content(content: &str) -> Cow<'_, str> {
+    let decoded_content = html_escape::decode_html_entities(content);
+    let encoded_content = html_escape::encode_safe(&decoded_content);
+    if content != encoded_content {
+        Cow::Owned(encoded_content.into_owned())
+    } else {
+        Cow::Borrowed(content)
+    }
+}
+
+/// Splits a URL with a non-http(s) scheme, like `mailto:` or `tel:`, into scheme and remainder.
+fn split_non_http_scheme(url: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = url.split_once(':')?;
+    if !scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        || scheme.eq_ignore_ascii_case("http")
+        || scheme.eq_ignore_ascii_case("https")
+    {
+        return None;
+    }
+    Some((scheme, rest))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use rari_types::fm_types::PageType;
+
+    use super::*;
+    use crate::pages::types::spa::SPA;
+
+    fn modifier<'a>() -> LinkModifier<'a> {
+        LinkModifier {
+            badges: &[],
+            badge_locale: Locale::EnUs,
+            code: false,
+            only_en_us: false,
+            rari_link: None,
+            base_url: None,
+            raw_anchor: false,
+        }
+    }
+
+    #[test]
+    fn test_safe_link_content() {
+        assert!(matches!(safe_link_content("Foo"), Cow::Borrowed("Foo")));
+        assert_eq!(safe_link_content("Foo & Bar"), "Foo &amp; Bar");
+        assert!(matches!(
+            safe_link_content("Foo &amp; Bar"),
+            Cow::Borrowed("Foo &amp; Bar")
+        ));
+        assert_eq!(safe_link_content("Foo &amp;amp; Bar"), "Foo &amp;amp; Bar");
+        assert_eq!(safe_link_content("&lt;a&gt;"), "&lt;a&gt;");
+        assert_eq!(safe_link_content("<a>"), "&lt;a&gt;");
+        assert_eq!(
+            safe_link_content("Array<T> & Map<K, V>"),
+            "Array&lt;T&gt; &amp; Map&lt;K, V&gt;"
+        );
+    }
+
+    #[test]
+    fn test_summary_to_title() {
+        assert_eq!(
+            summary_to_title(
+                "<p>A <a href=\"/en-US/docs/Glossary/HTML\"><strong>markup</strong></a>\n  language &amp; more.</p>"
+            ),
+            "A markup language & more."
+        );
+        assert_eq!(
+            summary_to_title("<p>The <code>&lt;dialog&gt;</code> element</p>"),
+            "The <dialog> element"
+        );
+    }
+
+    #[test]
+    fn test_render_internal_link_rari_link() -> Result<(), DocError> {
+        let mut out = String::new();
+        render_internal_link(
+            &mut out,
+            "/en-US/docs/Foo",
+            None,
+            "Foo",
+            None,
+            &modifier(),
+            false,
+        )?;
+        assert_eq!(out, r#"<a href="/en-US/docs/Foo">Foo</a>"#);
+
+        let mut out = String::new();
+        let modifier = LinkModifier {
+            rari_link: Some(RariLink::Plain),
+            ..modifier()
+        };
+        render_internal_link(
+            &mut out,
+            "/en-US/docs/Foo",
+            None,
+            "Foo",
+            None,
+            &modifier,
+            false,
+        )?;
+        assert_eq!(out, r#"<a href="/en-US/docs/Foo" data-rari-link>Foo</a>"#);
+
+        let mut out = String::new();
+        let modifier = LinkModifier {
+            rari_link: Some(RariLink::Category(PageCategory::Doc)),
+            ..modifier
+        };
+        render_internal_link(
+            &mut out,
+            "/en-US/docs/Foo",
+            None,
+            "Foo",
+            None,
+            &modifier,
+            false,
+        )?;
+        assert_eq!(
+            out,
+            r#"<a href="/en-US/docs/Foo" data-rari-link="doc">Foo</a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_internal_link_base_url() -> Result<(), DocError> {
+        let render = |url: &str, base_url: Option<&str>| -> Result<String, DocError> {
+            let mut out = String::new();
+            let modifier = LinkModifier {
+                base_url,
+                ..modifier()
+            };
+            render_internal_link(&mut out, url, None, "Foo", None, &modifier, false)?;
+            Ok(out)
+        };
+        assert_eq!(
+            render("/en-US/docs/Foo", None)?,
+            r#"<a href="/en-US/docs/Foo">Foo</a>"#
+        );
+        assert_eq!(
+            render("/en-US/docs/Foo", Some("https://developer.mozilla.org/"))?,
+            r#"<a href="https://developer.mozilla.org/en-US/docs/Foo">Foo</a>"#
+        );
+        assert_eq!(
+            render(
+                "https://developer.mozilla.org/en-US/docs/Foo",
+                Some("https://developer.mozilla.org")
+            )?,
+            r#"<a href="https://developer.mozilla.org/en-US/docs/Foo">Foo</a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_internal_link_raw_anchor() -> Result<(), DocError> {
+        let render = |raw_anchor: bool| -> Result<String, DocError> {
+            let mut out = String::new();
+            let modifier = LinkModifier {
+                raw_anchor,
+                ..modifier()
+            };
+            render_internal_link(
+                &mut out,
+                "/en-US/docs/Foo",
+                Some("Using_the_API"),
+                "Foo",
+                None,
+                &modifier,
+                false,
+            )?;
+            Ok(out)
+        };
+        assert_eq!(
+            render(false)?,
+            r#"<a href="/en-US/docs/Foo#using_the_api">Foo</a>"#
+        );
+        assert_eq!(
+            render(true)?,
+            r#"<a href="/en-US/docs/Foo#Using_the_API">Foo</a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_internal_link_only_en_us() -> Result<(), DocError> {
+        let mut out = String::new();
+        let modifier = LinkModifier {
+            only_en_us: true,
+            ..modifier()
+        };
+        render_internal_link(
+            &mut out,
+            "/en-US/docs/Foo",
+            None,
+            "Foo",
+            None,
+            &modifier,
+            false,
+        )?;
+        assert_eq!(
+            out,
+            r#"<a href="/en-US/docs/Foo" class="only-in-en-us" hreflang="en-US">Foo</a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_links_via_page() -> Result<(), DocError> {
+        let mut out = String::new();
+        let unresolved = render_links_via_page(
+            &mut out,
+            &[
+                LinkSpec {
+                    link: "mailto:foo@example.com",
+                    ..Default::default()
+                },
+                LinkSpec {
+                    link: "tel:+1-555-0100",
+                    content: Some("Call us"),
+                    ..Default::default()
+                },
+            ],
+            ", ",
+            Locale::EnUs,
+            false,
+        )?;
+        assert!(unresolved.is_empty());
+        assert_eq!(
+            out,
+            r#"<a href="mailto:foo@example.com">foo@example.com</a>, <a href="tel:+1-555-0100">Call us</a>"#
+        );
+        Ok(())
+    }
+
+    fn search_page() -> Page {
+        Page::SPA(Arc::new(SPA {
+            page_title: "Search",
+            slug: "search",
+            url: "/en-US/search".to_string(),
+            locale: Locale::EnUs,
+            page_type: PageType::SPA,
+            data: Default::default(),
+            base_slug: Cow::Borrowed("/en-US/"),
+            page_description: None,
+        }))
+    }
+
+    #[test]
+    fn test_render_link_via_page_query() -> Result<(), DocError> {
+        let search = search_page();
+        let mut requested = vec![];
+        let mut out = String::new();
+        render_link_via_page_with(
+            &mut out,
+            &LinkSpec {
+                link: "/en-US/search?q=grid#results",
+                ..Default::default()
+            },
+            Locale::EnUs,
+            Badges::None,
+            &mut |url| {
+                requested.push(url.to_string());
+                (url == "/en-US/search").then(|| search.clone())
+            },
+        )?;
+        assert_eq!(requested, ["/en-US/search"]);
+        assert_eq!(
+            out,
+            r#"<a href="/en-US/search?q=grid#results" data-templ-link>Search</a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_link_from_page_full() -> Result<(), DocError> {
+        let mut via_page = String::new();
+        render_link_via_page_with(
+            &mut via_page,
+            &LinkSpec {
+                link: "/en-US/search#Some results",
+                content: None,
+                code: true,
+                title: Some("Find"),
+            },
+            Locale::De,
+            Badges::Page,
+            &mut |_| Some(search_page()),
+        )?;
+        let mut from_page = String::new();
+        render_link_from_page_full(
+            &mut from_page,
+            &search_page(),
+            Some("Some results"),
+            None,
+            true,
+            Some("Find"),
+            Locale::De,
+            true,
+        )?;
+        assert_eq!(from_page, via_page);
+        assert_eq!(
+            from_page,
+            r#"<a href="/en-US/search#some_results" title="Find" class="only-in-en-us" hreflang="en-US" data-templ-link><code>Search</code></a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_explicit_badges() {
+        let search = search_page();
+        assert!(search.status().is_empty());
+        let modifier = page_link_modifier(
+            &search,
+            Badges::Explicit(&[FeatureStatus::Deprecated]),
+            false,
+            Locale::EnUs,
+        );
+        assert_eq!(modifier.badges, [FeatureStatus::Deprecated]);
+        let modifier = page_link_modifier(&search, Badges::Page, false, Locale::EnUs);
+        assert!(modifier.badges.is_empty());
+    }
+
+    #[test]
+    fn test_empty_link_content() -> Result<(), DocError> {
+        let mut out = String::new();
+        render_link_via_page_with(
+            &mut out,
+            &LinkSpec {
+                link: "/en-US/search",
+                content: Some(""),
+                ..Default::default()
+            },
+            Locale::EnUs,
+            Badges::None,
+            &mut |_| Some(search_page()),
+        )?;
+        assert_eq!(out, r#"<a href="/en-US/search" data-templ-link>Search</a>"#);
+
+        let mut out = String::new();
+        render_internal_link(
+            &mut out,
+            "/en-US/docs/Foo/",
+            None,
+            " ",
+            None,
+            &modifier(),
+            false,
+        )?;
+        assert_eq!(out, r#"<a href="/en-US/docs/Foo/">Foo</a>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_link_via_page_with_locale() -> Result<(), DocError> {
+        let mut requested = vec![];
+        for link in ["/fr/docs/Web/HTML", "/FR/docs/Web/HTML", "/Web/HTML"] {
+            render_link_via_page_with(
+                &mut String::new(),
+                &LinkSpec {
+                    link,
+                    ..Default::default()
+                },
+                Locale::EnUs,
+                Badges::None,
+                &mut |url| {
+                    requested.push(url.to_string());
+                    Some(search_page())
+                },
+            )?;
+        }
+        assert_eq!(
+            requested,
+            [
+                "/fr/docs/Web/HTML",
+                "/FR/docs/Web/HTML",
+                "/en-US/docs/Web/HTML"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_link_via_page_not_created() -> Result<(), DocError> {
+        let render = |link, title| -> Result<String, DocError> {
+            let mut out = String::new();
+            let resolved = render_link_via_page_with(
+                &mut out,
+                &LinkSpec {
+                    link,
+                    title,
+                    ..Default::default()
+                },
+                Locale::EnUs,
+                Badges::None,
+                &mut |_| None,
+            )?;
+            assert!(!resolved);
+            Ok(out)
+        };
+        let summary = html_escape::encode_quoted_attribute(l10n_json_data(
+            "Common",
+            "summary",
+            Locale::EnUs,
+        )?)
+        .into_owned();
+        assert_eq!(
+            render("/en-US/docs/Web/Nope", None)?,
+            format!(
+                r#"<a class="page-not-created" data-href="/en-US/docs/Web/Nope" title="{summary}">Nope</a>"#
+            )
+        );
+        // A given title is kept.
+        assert_eq!(
+            render("/en-US/docs/Web/Nope", Some("Nope & co"))?,
+            r#"<a class="page-not-created" data-href="/en-US/docs/Web/Nope" title="Nope &amp; co">Nope</a>"#
+        );
+        // Only missing docs are marked as not created.
+        assert_eq!(
+            render("/en-US/blog/nope", None)?,
+            r#"<a data-templ-link href="/en-US/blog/nope">nope</a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_link() -> Result<(), DocError> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        LINK_RECORDER_SENDER.set(tx).unwrap();
+        let source = "/en-US/docs/Test/Record_link";
+        {
+            let _source = LinkSource::enter(source);
+            for link in ["/en-US/docs/Web/Nope", "https://example.com/"] {
+                render_link_via_page_with(
+                    &mut String::new(),
+                    &LinkSpec {
+                        link,
+                        ..Default::default()
+                    },
+                    Locale::EnUs,
+                    Badges::None,
+                    &mut |_| None,
+                )?;
+            }
+        }
+        // Other tests may render links at the same time, on other threads.
+        let records = rx
+            .try_iter()
+            .flatten()
+            .filter(|record| record.source == source)
+            .map(|record| (record.target, record.is_internal, record.resolved))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            [
+                ("/en-US/docs/Web/Nope".to_string(), true, false),
+                ("https://example.com/".to_string(), false, true),
+            ]
+        );
+        // Keep the channel open for links rendered by later tests.
+        std::mem::forget(rx);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_link_via_page_mailto() -> Result<(), DocError> {
+        let mut out = String::new();
+        render_link_via_page(
+            &mut out,
+            "mailto:foo@example.com?subject=Hi",
+            Locale::EnUs,
+            None,
+            false,
+            None,
+            false,
+        )?;
+        assert_eq!(
+            out,
+            r#"<a href="mailto:foo@example.com?subject=Hi">foo@example.com</a>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_link_via_page_tel() -> Result<(), DocError> {
+        let mut out = String::new();
+        render_link_via_page(
+            &mut out,
+            "tel:+1-555-0100",
+            Locale::EnUs,
+            None,
+            false,
+            None,
+            false,
+        )?;
+        assert_eq!(out, r#"<a href="tel:+1-555-0100">+1-555-0100</a>"#);
+
+        let mut out = String::new();
+        render_link_via_page(
+            &mut out,
+            "tel:+1-555-0100",
+            Locale::EnUs,
+            Some("Call us"),
+            false,
+            None,
+            false,
+        )?;
+        assert_eq!(out, r#"<a href="tel:+1-555-0100">Call us</a>"#);
+        Ok(())
+    }
 }
@@ -7,7 +7,7 @@ use constcat::concat;
 use dashmap::DashMap;
 use indexmap::IndexMap;
 use rari_types::fm_types::PageType;
-use rari_types::globals::cache_content;
+use rari_types::globals::{cache_content, refresh_cache};
 use rari_types::locale::{default_locale, Locale};
 use rari_utils::concat_strs;
 use scraper::{Html, Node, Selector};
@@ -107,19 +107,20 @@ pub fn postprocess_sidebar<T: PageLike>(
 
 pub fn render_sidebar(s: &str, slug: &str, locale: Locale) -> Result<String, DocError> {
     let rendered_sidebar = if cache_side_bar(s) {
-        if let Some(sb) = SIDEBAR_CACHE
-            .get(&locale)
-            .and_then(|map| map.get(s).map(ToString::to_string))
-        {
-            return Ok::<_, DocError>(sb);
+        if !refresh_cache() {
+            if let Some(sb) = SIDEBAR_CACHE
+                .get(&locale)
+                .and_then(|map| map.get(s).map(ToString::to_string))
+            {
+                return Ok::<_, DocError>(sb);
+            }
         }
         let sidebar = read_sidebar(s, locale, slug)?;
         let rendered_sidebar = sidebar.render(s, locale)?;
         SIDEBAR_CACHE
             .entry(locale)
             .or_default()
-            .entry(s.to_string())
-            .or_insert(rendered_sidebar.clone());
+            .insert(s.to_string(), rendered_sidebar.clone());
         rendered_sidebar
     } else {
         let sidebar = read_sidebar(s, locale, slug)?;
@@ -637,6 +638,9 @@ impl SidebarMetaEntry {
                         badge_locale: page.locale(),
                         code: self.code,
                         only_en_us: page.locale() != locale,
+                        rari_link: None,
+                        base_url: None,
+                        raw_anchor: false,
                     },
                 )?;
             }
@@ -22,6 +22,7 @@ use crate::error::DocError;
 use crate::helpers::parents::parents;
 use crate::helpers::title::{page_title, transform_title};
 use crate::html::bubble_up::bubble_up_curriculum_page;
+use crate::html::links::LinkSource;
 use crate::html::modifier::{add_missing_ids, insert_self_links_for_dts, remove_empty_p};
 use crate::html::rewriter::{post_process_html, post_process_inline_sidebar};
 use crate::html::sections::{split_sections, BuildSection, BuildSectionType, Split};
@@ -446,6 +447,7 @@ pub(crate) fn copy_additional_files(from: &Path, to: &Path, ignore: &Path) -> Re
 
 impl PageBuilder for Page {
     fn build(&self) -> Result<BuiltPage, DocError> {
+        let _link_source = LinkSource::enter(self.url());
         match self {
             Self::Doc(doc) => build_doc(doc),
             Self::BlogPost(post) => build_blog_post(post),
@@ -50,7 +50,33 @@ pub enum PageCategory {
     GenericPage,
 }
 
+impl PageCategory {
+    /// Returns a stable, kebab-cased name for the category, e.g. for use in HTML attributes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PageCategory::Doc => "doc",
+            PageCategory::BlogPost => "blog-post",
+            PageCategory::SPA => "spa",
+            PageCategory::Curriculum => "curriculum",
+            PageCategory::ContributorSpotlight => "contributor-spotlight",
+            PageCategory::GenericPage => "generic-page",
+        }
+    }
+}
+
 impl Page {
+    /// Returns the `PageCategory` of this page.
+    pub fn page_category(&self) -> PageCategory {
+        match self {
+            Page::Doc(_) => PageCategory::Doc,
+            Page::BlogPost(_) => PageCategory::BlogPost,
+            Page::SPA(_) => PageCategory::SPA,
+            Page::Curriculum(_) => PageCategory::Curriculum,
+            Page::ContributorSpotlight(_) => PageCategory::ContributorSpotlight,
+            Page::GenericPage(_) => PageCategory::GenericPage,
+        }
+    }
+
     /// Creates an instance of `Page` from the given URL if it exists.
     ///
     /// # Arguments
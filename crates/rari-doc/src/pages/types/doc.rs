@@ -19,7 +19,7 @@ use validator::Validate;
 use crate::cached_readers::{doc_page_from_static_files, CACHED_DOC_PAGE_FILES};
 use crate::error::DocError;
 use crate::pages::page::{Page, PageCategory, PageLike, PageReader, PageWriter};
-use crate::resolve::{build_url, url_to_folder_path};
+use crate::resolve::{build_url, folder_path_to_url_path, url_to_folder_path};
 use crate::utils::{
     locale_and_typ_from_path, root_for_locale, serialize_t_or_vec, split_fm, t_or_vec,
 };
@@ -320,14 +320,14 @@ fn read_doc(path: impl Into<PathBuf>) -> Result<Doc, DocError> {
                 locale.as_folder_str(),
                 "/",
                 folder_path
-                    .map(|path| path.to_string_lossy())
+                    .map(folder_path_to_url_path)
                     .unwrap_or_default()
-                    .as_ref()
+                    .as_str()
             ),
             concat_strs!(
                 locale.as_folder_str(),
                 "/",
-                folder_path_from_slug.to_string_lossy().as_ref()
+                folder_path_to_url_path(&folder_path_from_slug).as_str()
             ),
         ));
     }
@@ -43,39 +43,44 @@ impl SPA {
     }
 
     pub fn from_slug(slug: &str, locale: Locale) -> Option<Page> {
-        BASIC_SPAS.get(slug).and_then(|build_spa| {
-            if build_spa.en_us_only && locale != Locale::EnUs {
-                None
-            } else {
-                Some(Page::SPA(Arc::new(SPA {
-                    page_title: &build_spa.page_title,
-                    slug: &build_spa.slug,
-                    url: concat_strs!(
-                        "/",
-                        locale.as_url_str(),
-                        "/",
-                        &build_spa.slug,
-                        if build_spa.trailing_slash && !build_spa.slug.is_empty() {
-                            "/"
-                        } else {
-                            ""
-                        }
-                    ),
-                    locale,
-                    page_type: PageType::SPA,
-                    data: build_spa.data,
-                    base_slug: Cow::Owned(concat_strs!("/", locale.as_url_str(), "/")),
-                    page_description: build_spa.page_description.as_deref(),
-                })))
-            }
+        SPA::lookup(slug, locale).map(|build_spa| {
+            Page::SPA(Arc::new(SPA {
+                page_title: &build_spa.page_title,
+                slug: &build_spa.slug,
+                url: SPA::url_for(build_spa, locale),
+                locale,
+                page_type: PageType::SPA,
+                data: build_spa.data,
+                base_slug: Cow::Owned(concat_strs!("/", locale.as_url_str(), "/")),
+                page_description: build_spa.page_description.as_deref(),
+            }))
         })
     }
 
     pub fn is_spa(slug: &str, locale: Locale) -> bool {
+        SPA::lookup(slug, locale).is_some()
+    }
+
+    /// Looks up the SPA for `slug` if it exists in `locale`.
+    pub(crate) fn lookup(slug: &str, locale: Locale) -> Option<&'static BuildSPA> {
         BASIC_SPAS
             .get(slug)
-            .map(|build_spa| locale == Default::default() || !build_spa.en_us_only)
-            .unwrap_or_default()
+            .filter(|build_spa| locale == Default::default() || !build_spa.en_us_only)
+    }
+
+    /// The canonical URL of a looked up SPA in `locale`.
+    pub(crate) fn url_for(build_spa: &BuildSPA, locale: Locale) -> String {
+        concat_strs!(
+            "/",
+            locale.as_url_str(),
+            "/",
+            &build_spa.slug,
+            if build_spa.trailing_slash && !build_spa.slug.is_empty() {
+                "/"
+            } else {
+                ""
+            }
+        )
     }
 
     pub fn all() -> Vec<(String, Locale)> {
@@ -8,20 +8,25 @@
 //!
 //! - **Functions**:
 //!   - `url_to_folder_path`: Converts a URL slug to a folder path by replacing certain characters.
+//!   - `folder_path_to_url_path`: Converts a folder path back to a `/`-separated string.
 //!   - `strip_locale_from_url`: Strips the locale from a URL and returns the locale and the remaining URL.
+//!   - `parse_url`: Splits a URL into locale, page category, slug, and fragment.
 //!   - `url_meta_from`: Extracts metadata from a URL, including the locale, slug, and page category.
+//!   - `url_meta_and_canonical_url_from`: Like `url_meta_from`, also returning the canonical URL.
 //!
 //! - **Structs**:
 //!   - `UrlMeta`: A struct that holds metadata extracted from a URL, including the folder path, slug, locale, and page category.
 
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
 
+use rari_md::anchor::anchorize;
 use rari_types::locale::Locale;
 use rari_utils::concat_strs;
 
+use crate::cached_readers::BuildSPA;
 use crate::error::{DocError, UrlError};
-use crate::pages::page::{PageCategory, PageLike};
+use crate::pages::page::PageCategory;
 use crate::pages::types::generic::GenericPage;
 use crate::pages::types::spa::SPA;
 
@@ -34,8 +39,16 @@ use crate::pages::types::spa::SPA;
 /// - `::` is replaced with `_doublecolon_`
 /// - `:` is replaced with `_colon_`
 /// - `?` is replaced with `_question_`
+/// - `<` is replaced with `_lt_`
+/// - `>` is replaced with `_gt_`
+/// - `|` is replaced with `_pipe_`
+/// - `"` is replaced with `_quote_`
 ///
-/// The resulting string is then converted to lowercase and returned as a `PathBuf`.
+/// `::` is replaced before `:` so it doesn't end up as two `_colon_`s. None of the replacements contain a
+/// character that is replaced afterwards.
+///
+/// The resulting string is then converted to lowercase and returned as a `PathBuf`. Slugs always use `/`,
+/// the segments are joined with the platform's separator.
 ///
 /// # Arguments
 ///
@@ -45,13 +58,40 @@ use crate::pages::types::spa::SPA;
 ///
 /// * `PathBuf` - Returns a `PathBuf` representing the converted folder path.
 pub fn url_to_folder_path(slug: &str) -> PathBuf {
-    PathBuf::from(
-        slug.replace('*', "_star_")
-            .replace("::", "_doublecolon_")
-            .replace(':', "_colon_")
-            .replace('?', "_question_")
-            .to_lowercase(),
-    )
+    slug.replace('*', "_star_")
+        .replace("::", "_doublecolon_")
+        .replace(':', "_colon_")
+        .replace('?', "_question_")
+        .replace('<', "_lt_")
+        .replace('>', "_gt_")
+        .replace('|', "_pipe_")
+        .replace('"', "_quote_")
+        .to_lowercase()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Converts a folder path to a `/`-separated string, independent of the platform's separator.
+///
+/// This is the counterpart of [`url_to_folder_path`] for going back from the file system to URL space. Only
+/// normal components are kept, so roots, prefixes and `.` are dropped.
+///
+/// # Arguments
+///
+/// * `path` - The folder path to be converted.
+///
+/// # Returns
+///
+/// * `String` - The path segments joined with `/`.
+pub fn folder_path_to_url_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 /// Strips the locale from a URL and returns the locale and the remaining URL.
@@ -68,6 +108,8 @@ pub fn url_to_folder_path(slug: &str) -> PathBuf {
 ///
 /// * `(Option<Locale>, &str)` - Returns a tuple where the first element is an `Option<Locale>` containing the locale
 ///   if found, and the second element is the remaining part of the URL.
+///
+/// This is also the locale step of [`parse_url`], so both always agree on what is a locale.
 pub(crate) fn strip_locale_from_url(url: &str) -> (Option<Locale>, &str) {
     if url.len() < 2 || !url.starts_with('/') {
         return (None, url);
@@ -77,6 +119,10 @@ pub(crate) fn strip_locale_from_url(url: &str) -> (Option<Locale>, &str) {
     (locale, &url[if locale.is_none() { 0 } else { i }..])
 }
 
+/// First path segments that indicate a URL was written without a locale, e.g. `/docs/Web/HTML`.
+/// Such URLs resolve against the default locale.
+const LOCALE_LESS_PREFIXES: &[&str] = &["docs", "blog", "curriculum", "community"];
+
 /// Represents metadata extracted from a URL.
 ///
 /// The `UrlMeta` struct holds various pieces of data that are extracted from a URL,
@@ -100,7 +146,8 @@ pub struct UrlMeta<'a> {
 /// This function parses the given URL to extract various pieces of metadata, such as the locale,
 /// slug, and page category. It supports different URL structures for documentation pages, blog posts,
 /// curriculum pages, community spotlight pages, single-page applications (SPA), and generic pages.
-/// If the URL does not match any known patterns, it returns an `UrlError::InvalidUrl` error.
+/// If the URL does not match any known patterns, it returns an `UrlError::UnknownCategory` error.
+/// URLs without a locale segment (e.g. `/docs/Web/HTML`) resolve to the default locale.
 ///
 /// # Arguments
 ///
@@ -114,14 +161,121 @@ pub struct UrlMeta<'a> {
 /// # Errors
 ///
 /// This function will return an error if:
-/// - The URL does not contain a recognizable locale.
+/// - The URL is not an absolute path (`UrlError::MalformedUrl`).
+/// - The URL does not contain a recognizable locale (`UrlError::UnknownLocale`).
 /// - The URL does not match any known patterns for documentation pages, blog posts, curriculum pages, etc.
+///   (`UrlError::UnknownCategory`).
 pub fn url_meta_from(url: &str) -> Result<UrlMeta<'_>, UrlError> {
-    let mut split = url[..url.find('#').unwrap_or(url.len())]
-        .splitn(4, '/')
-        .skip(1);
-    let locale: Locale = Locale::from_str(split.next().unwrap_or_default())?;
-    let tail: Vec<_> = split.collect();
+    url_meta_and_spa_from(url).map(|(meta, _)| meta)
+}
+
+/// Extracts metadata from a URL together with the page's canonical URL.
+///
+/// This works like [`url_meta_from`], but for SPAs the canonical URL is derived from the same SPA lookup
+/// that classified the URL, instead of looking it up again as [`build_url`] does.
+///
+/// # Arguments
+///
+/// * `url` - A string slice that holds the URL to be processed.
+///
+/// # Returns
+///
+/// * `Result<(UrlMeta, String), DocError>` - Returns the `UrlMeta` and the canonical URL if successful,
+///   or a `DocError` if the URL is invalid.
+pub fn url_meta_and_canonical_url_from(url: &str) -> Result<(UrlMeta<'_>, String), DocError> {
+    let (meta, spa) = url_meta_and_spa_from(url)?;
+    let canonical_url = match spa {
+        Some(build_spa) => SPA::url_for(build_spa, meta.locale),
+        None => build_url(meta.slug, meta.locale, meta.page_category)?,
+    };
+    Ok((meta, canonical_url))
+}
+
+fn url_meta_and_spa_from(url: &str) -> Result<(UrlMeta<'_>, Option<&'static BuildSPA>), UrlError> {
+    let (
+        ParsedUrl {
+            locale,
+            category,
+            slug,
+            ..
+        },
+        spa,
+    ) = parse_url_and_spa(url)?;
+    Ok((
+        UrlMeta {
+            folder_path: url_to_folder_path(slug),
+            slug,
+            locale,
+            page_category: category,
+        },
+        spa,
+    ))
+}
+
+/// The parts of a URL, see [`parse_url`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedUrl<'a> {
+    pub locale: Locale,
+    pub category: PageCategory,
+    pub slug: &'a str,
+    /// The fragment without the leading `#`.
+    pub fragment: Option<&'a str>,
+    /// The output file the URL points into, like `index.json` for `/en-US/docs/Web/index.json`.
+    pub suffix: Option<&'static str>,
+}
+
+/// Files served or written next to a page, which URLs may point into.
+const OUTPUT_FILES: &[&str] = &["index.json", "contributors.txt"];
+
+/// Splits a URL into locale, page category, slug and fragment in one pass.
+///
+/// This is the URL parser underlying [`url_meta_from`], the locale is split off with [`strip_locale_from_url`].
+/// URLs without a locale segment (e.g. `/docs/Web/HTML`) resolve to the default locale. A trailing output file
+/// like `/index.json` is split off into `suffix`.
+///
+/// # Arguments
+///
+/// * `url` - A string slice that holds the URL to be processed.
+///
+/// # Returns
+///
+/// * `Result<ParsedUrl<'_>, UrlError>` - Returns the parts of the URL if successful, or an `UrlError` if the
+///   URL is invalid or does not match any known patterns.
+pub fn parse_url(url: &str) -> Result<ParsedUrl<'_>, UrlError> {
+    parse_url_and_spa(url).map(|(parsed, _)| parsed)
+}
+
+fn parse_url_and_spa(url: &str) -> Result<(ParsedUrl<'_>, Option<&'static BuildSPA>), UrlError> {
+    let (url_no_hash, fragment) = match url.split_once('#') {
+        Some((url, fragment)) => (url, Some(fragment)),
+        None => (url, None),
+    };
+    if !url_no_hash.starts_with('/') {
+        return Err(UrlError::MalformedUrl(url.to_string()));
+    }
+    let (url_no_hash, suffix) = OUTPUT_FILES
+        .iter()
+        .find_map(|file| {
+            url_no_hash
+                .strip_suffix(file)
+                .and_then(|url| url.strip_suffix('/'))
+                .map(|url| (url, Some(*file)))
+        })
+        .unwrap_or((url_no_hash, None));
+    let (locale, rest) = match strip_locale_from_url(url_no_hash) {
+        (Some(locale), rest) => (locale, rest),
+        (None, rest) => {
+            let first = rest.split('/').nth(1).unwrap_or_default();
+            if !LOCALE_LESS_PREFIXES.contains(&first) {
+                return Err(UrlError::UnknownLocale(first.to_string()));
+            }
+            // Shorthand like `/docs/Web/HTML`, resolving against the default locale.
+            (Default::default(), rest)
+        }
+    };
+    let slug = rest.strip_prefix('/').unwrap_or(rest);
+    let tail = slug.splitn(2, '/').collect::<Vec<_>>();
+    let mut spa = None;
     let (page_category, slug) = match tail.as_slice() {
         ["docs", tail] => (PageCategory::Doc, *tail),
         ["blog"] | ["blog", ""] if locale == Default::default() => (PageCategory::SPA, "blog"),
@@ -130,25 +284,46 @@ pub fn url_meta_from(url: &str) -> Result<UrlMeta<'_>, UrlError> {
         ["community", tail] if locale == Default::default() && tail.starts_with("spotlight") => {
             (PageCategory::ContributorSpotlight, *tail)
         }
+        // Everything else, including other `community/…` pages like `community/discussions`, has to be
+        // a SPA or generic page.
         _ => {
-            let (_, slug) = strip_locale_from_url(url);
-            let slug = slug.strip_prefix('/').unwrap_or(slug);
-            if SPA::is_spa(slug, locale) {
+            if let Some(build_spa) = SPA::lookup(slug, locale) {
+                spa = Some(build_spa);
                 (PageCategory::SPA, slug)
             } else if GenericPage::is_generic(slug, locale) {
                 (PageCategory::GenericPage, slug)
+            } else if let Some((spa_slug, build_spa)) = spa_prefix(slug, locale) {
+                // Sub-routes like `play/abc` are handled client-side by the SPA's shell.
+                spa = Some(build_spa);
+                (PageCategory::SPA, spa_slug)
             } else {
-                return Err(UrlError::InvalidUrl);
+                return Err(UrlError::UnknownCategory(slug.to_string()));
             }
         }
     };
-    let folder_path = url_to_folder_path(slug);
-    Ok(UrlMeta {
-        folder_path,
-        slug,
-        locale,
-        page_category,
-    })
+    Ok((
+        ParsedUrl {
+            locale,
+            category: page_category,
+            slug,
+            fragment,
+            suffix,
+        },
+        spa,
+    ))
+}
+
+/// The longest proper prefix of `slug` (at a `/`) that is a SPA in `locale`, e.g. `observatory/analyze`
+/// for `observatory/analyze/results`. The home page SPA (empty slug) is never a prefix.
+fn spa_prefix(slug: &str, locale: Locale) -> Option<(&str, &'static BuildSPA)> {
+    let mut prefix = slug;
+    while let Some((head, _)) = prefix.rsplit_once('/') {
+        prefix = head;
+        if let Some(build_spa) = SPA::lookup(prefix, locale).filter(|_| !prefix.is_empty()) {
+            return Some((prefix, build_spa));
+        }
+    }
+    None
 }
 
 /// Extracts the `Locale` from a given URL path.
@@ -251,20 +426,52 @@ pub fn build_url(slug: &str, locale: Locale, typ: PageCategory) -> Result<String
     Ok(match typ {
         PageCategory::Doc => concat_strs!("/", locale.as_url_str(), "/docs/", slug),
         PageCategory::BlogPost => concat_strs!("/", locale.as_url_str(), "/blog/", slug, "/"),
-        PageCategory::SPA => SPA::from_slug(slug, locale)
-            .ok_or(DocError::PageNotFound(slug.to_string(), PageCategory::SPA))?
-            .url()
-            .to_owned(),
+        PageCategory::SPA => SPA::lookup(slug, locale)
+            .map(|build_spa| SPA::url_for(build_spa, locale))
+            .ok_or(DocError::PageNotFound(slug.to_string(), PageCategory::SPA))?,
         PageCategory::Curriculum => {
             concat_strs!("/", locale.as_url_str(), "/curriculum/", slug, "/")
         }
+        // Contributor spotlight slugs include the `spotlight/` prefix.
         PageCategory::ContributorSpotlight => {
-            concat_strs!("/", locale.as_url_str(), "/community/spotlight/", slug)
+            concat_strs!("/", locale.as_url_str(), "/community/", slug)
         }
         PageCategory::GenericPage => concat_strs!("/", locale.as_url_str(), "/", slug),
     })
 }
 
+/// Builds a URL for a given slug, locale, and page category, with an optional fragment.
+///
+/// This works like [`build_url`] but appends `#` followed by the anchorized `anchor`,
+/// so fragments are normalized the same way as heading ids.
+///
+/// # Arguments
+///
+/// * `slug` - A string slice that holds the slug of the page.
+/// * `locale` - A `Locale` that specifies the locale of the page.
+/// * `typ` - A `PageCategory` that specifies the category of the page.
+/// * `anchor` - An optional fragment to append (without the leading `#`).
+///
+/// # Returns
+///
+/// * `Result<String, DocError>` - Returns the constructed URL as a `String` if successful,
+///   or a `DocError` if an error occurs (e.g., if the SPA slug is not found).
+pub fn build_url_with_anchor(
+    slug: &str,
+    locale: Locale,
+    typ: PageCategory,
+    anchor: Option<&str>,
+) -> Result<String, DocError> {
+    let url = build_url(slug, locale, typ)?;
+    Ok(match anchor {
+        Some(anchor) => {
+            let anchor = anchorize(anchor);
+            concat_strs!(&url, "#", &anchor)
+        }
+        None => url,
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -284,6 +491,194 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_url_to_path_without_locale() -> Result<(), UrlError> {
+        let url = "/docs/Web/HTML";
+        let UrlMeta {
+            folder_path,
+            slug,
+            locale,
+            page_category,
+        } = url_meta_from(url)?;
+        assert_eq!(locale, Locale::EnUs);
+        assert_eq!(folder_path, PathBuf::from("web/html"));
+        assert_eq!(slug, "Web/HTML");
+        assert_eq!(page_category, PageCategory::Doc);
+        Ok(())
+    }
+
+    #[test]
+    fn test_url_meta_and_canonical_url() -> Result<(), DocError> {
+        let (UrlMeta { slug, locale, .. }, canonical_url) =
+            url_meta_and_canonical_url_from("/docs/Web/HTML#syntax")?;
+        assert_eq!(locale, Locale::EnUs);
+        assert_eq!(slug, "Web/HTML");
+        assert_eq!(canonical_url, "/en-US/docs/Web/HTML");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_url() -> Result<(), UrlError> {
+        assert_eq!(
+            parse_url("/de/docs/Web/HTML#syntax")?,
+            ParsedUrl {
+                locale: Locale::De,
+                category: PageCategory::Doc,
+                slug: "Web/HTML",
+                fragment: Some("syntax"),
+                suffix: None,
+            }
+        );
+        assert_eq!(
+            parse_url("/docs/Web/HTML")?,
+            ParsedUrl {
+                locale: Locale::EnUs,
+                category: PageCategory::Doc,
+                slug: "Web/HTML",
+                fragment: None,
+                suffix: None,
+            }
+        );
+        assert_eq!(
+            parse_url("/fr/docs/Web/HTML/index.json")?,
+            ParsedUrl {
+                locale: Locale::Fr,
+                category: PageCategory::Doc,
+                slug: "Web/HTML",
+                fragment: None,
+                suffix: Some("index.json"),
+            }
+        );
+        assert_eq!(parse_url("/en-US/docs/Web/contributors.txt")?.slug, "Web");
+        assert_eq!(
+            parse_url("/en-US/blog/#latest")?,
+            ParsedUrl {
+                locale: Locale::EnUs,
+                category: PageCategory::SPA,
+                slug: "blog",
+                fragment: Some("latest"),
+                suffix: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_spa_sub_routes() -> Result<(), DocError> {
+        let parsed = parse_url("/en-US/observatory/analyze/results")?;
+        assert_eq!(parsed.category, PageCategory::SPA);
+        assert_eq!(parsed.slug, "observatory/analyze");
+        let (meta, canonical_url) = url_meta_and_canonical_url_from("/en-US/play/abc/")?;
+        assert_eq!(meta.page_category, PageCategory::SPA);
+        assert_eq!(meta.slug, "play");
+        assert_eq!(canonical_url, "/en-US/play");
+        assert_eq!(
+            build_url(meta.slug, meta.locale, meta.page_category)?,
+            canonical_url
+        );
+        assert!(matches!(
+            parse_url("/en-US/plus/unknown"),
+            Err(UrlError::UnknownCategory(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_url_errors() {
+        assert!(matches!(
+            parse_url("/xx/docs/Web"),
+            Err(UrlError::UnknownLocale(locale)) if locale == "xx"
+        ));
+        assert!(matches!(
+            parse_url("en-US/docs/Web"),
+            Err(UrlError::MalformedUrl(_))
+        ));
+        assert!(matches!(parse_url(""), Err(UrlError::MalformedUrl(_))));
+    }
+
+    #[test]
+    fn test_community_round_trip() -> Result<(), DocError> {
+        let url = "/en-US/community/spotlight/jane";
+        let UrlMeta {
+            slug,
+            locale,
+            page_category,
+            ..
+        } = url_meta_from(url)?;
+        assert_eq!(page_category, PageCategory::ContributorSpotlight);
+        assert_eq!(slug, "spotlight/jane");
+        assert_eq!(build_url(slug, locale, page_category)?, url);
+        let url = "/en-US/community/discussions";
+        let UrlMeta {
+            slug,
+            locale,
+            page_category,
+            ..
+        } = url_meta_from(url)?;
+        assert_eq!(page_category, PageCategory::GenericPage);
+        assert_eq!(slug, "community/discussions");
+        assert_eq!(build_url(slug, locale, page_category)?, url);
+        Ok(())
+    }
+
+    #[test]
+    fn test_folder_path_separators() {
+        let folder_path = url_to_folder_path("Web/CSS/::after");
+        assert_eq!(
+            folder_path,
+            ["web", "css", "_doublecolon_after"]
+                .iter()
+                .collect::<PathBuf>()
+        );
+        assert_eq!(
+            folder_path_to_url_path(&folder_path),
+            "web/css/_doublecolon_after"
+        );
+        let native = format!("web{}css", std::path::MAIN_SEPARATOR);
+        assert_eq!(folder_path_to_url_path(Path::new(&native)), "web/css");
+        assert_eq!(folder_path_to_url_path(Path::new("web/css")), "web/css");
+    }
+
+    #[test]
+    fn test_folder_path_reserved_chars() {
+        assert_eq!(
+            url_to_folder_path("Web/JavaScript/Reference/Operators/<<=>>"),
+            [
+                "web",
+                "javascript",
+                "reference",
+                "operators",
+                "_lt__lt_=_gt__gt_"
+            ]
+            .iter()
+            .collect::<PathBuf>()
+        );
+        assert_eq!(
+            url_to_folder_path("Glossary/a|\"b\"::c:d"),
+            ["glossary", "a_pipe__quote_b_quote__doublecolon_c_colon_d"]
+                .iter()
+                .collect::<PathBuf>()
+        );
+    }
+
+    #[test]
+    fn test_build_url_with_anchor() -> Result<(), DocError> {
+        assert_eq!(
+            build_url_with_anchor("Web/HTML", Locale::EnUs, PageCategory::Doc, None)?,
+            "/en-US/docs/Web/HTML"
+        );
+        assert_eq!(
+            build_url_with_anchor(
+                "Web/HTML",
+                Locale::EnUs,
+                PageCategory::Doc,
+                Some("Browser compatibility")
+            )?,
+            "/en-US/docs/Web/HTML#browser_compatibility"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_from_url() {
         let url = "/en-US/docs/Web";
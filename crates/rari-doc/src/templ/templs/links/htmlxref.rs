@@ -1,7 +1,10 @@
 use rari_templ_func::rari_f;
 use rari_types::AnyArg;
+use rari_utils::concat_strs;
 
 use crate::error::DocError;
+use crate::pages::page::PageCategory;
+use crate::resolve::build_url_with_anchor;
 use crate::templ::api::RariApi;
 
 #[rari_f]
@@ -17,17 +20,14 @@ pub fn htmlxref(
         code = true;
         format!("&lt;{element_name}&gt;")
     });
-    let mut url = format!(
-        "/{}/docs/Web/HTML/Element/{}",
-        env.locale.as_url_str(),
-        element_name,
-    );
-    if let Some(anchor) = anchor {
-        if !anchor.starts_with('#') {
-            url.push('#');
-        }
-        url.push_str(&anchor);
-    }
+    let url = build_url_with_anchor(
+        &concat_strs!("Web/HTML/Element/", &element_name),
+        env.locale,
+        PageCategory::Doc,
+        anchor
+            .as_deref()
+            .map(|anchor| anchor.trim_start_matches('#')),
+    )?;
 
     RariApi::link(&url, env.locale, Some(display.as_ref()), code, None, false)
 }
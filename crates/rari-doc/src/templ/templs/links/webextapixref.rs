@@ -2,8 +2,11 @@ use std::borrow::Cow;
 
 use rari_templ_func::rari_f;
 use rari_types::AnyArg;
+use rari_utils::concat_strs;
 
 use crate::error::DocError;
+use crate::pages::page::PageCategory;
+use crate::resolve::build_url_with_anchor;
 use crate::templ::api::RariApi;
 
 #[rari_f]
@@ -15,18 +18,21 @@ pub fn webextapixref(
 ) -> Result<String, DocError> {
     let display = display.as_deref().filter(|s| !s.is_empty());
     let mut display = display.map(Cow::Borrowed).unwrap_or(Cow::Borrowed(&api));
-    let mut url = format!(
-        "/{}/docs/Mozilla/Add-ons/WebExtensions/API/{}",
-        env.locale.as_url_str(),
-        &api.replace(' ', "_").replace("()", "").replace('.', "/"),
-    );
+    let anchor = anchor
+        .as_deref()
+        .map(|anchor| anchor.trim_start_matches('#'));
+    let url = build_url_with_anchor(
+        &concat_strs!(
+            "Mozilla/Add-ons/WebExtensions/API/",
+            &api.replace(' ', "_").replace("()", "").replace('.', "/")
+        ),
+        env.locale,
+        PageCategory::Doc,
+        anchor,
+    )?;
     if let Some(anchor) = anchor {
-        if !anchor.starts_with('#') {
-            url.push('#');
-        }
-        url.push_str(&anchor);
         display.to_mut().push('#');
-        display.to_mut().push_str(&anchor);
+        display.to_mut().push_str(anchor);
     };
 
     RariApi::link(
@@ -49,6 +49,9 @@ pub fn list_subpages_for_sidebar(
                 badge_locale: env.locale,
                 code,
                 only_en_us: locale_page.locale() != env.locale,
+                rari_link: None,
+                base_url: None,
+                raw_anchor: false,
             },
             true,
         )?;
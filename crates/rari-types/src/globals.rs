@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{LazyLock, OnceLock};
@@ -10,9 +11,33 @@ use crate::locale::Locale;
 use crate::settings::{Deps, Settings};
 use crate::{HistoryEntry, Popularities};
 
+thread_local! {
+    static CONTENT_ROOT_OVERRIDE: Cell<Option<&'static Path>> = const { Cell::new(None) };
+    static CACHE_CONTENT_OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+    static REFRESH_CACHE: Cell<bool> = const { Cell::new(false) };
+}
+
 #[inline(always)]
 pub fn content_root() -> &'static Path {
-    settings().content_root.as_path()
+    CONTENT_ROOT_OVERRIDE
+        .get()
+        .unwrap_or_else(|| settings().content_root.as_path())
+}
+
+/// Restores the previous content root override when dropped.
+#[must_use]
+pub struct ContentRootOverride(Option<&'static Path>);
+
+impl Drop for ContentRootOverride {
+    fn drop(&mut self) {
+        CONTENT_ROOT_OVERRIDE.set(self.0);
+    }
+}
+
+/// Make `content_root()` return `root` on the current thread until the returned
+/// guard is dropped. Work spawned onto other threads still sees the configured root.
+pub fn override_content_root(root: &'static Path) -> ContentRootOverride {
+    ContentRootOverride(CONTENT_ROOT_OVERRIDE.replace(Some(root)))
 }
 
 #[inline(always)]
@@ -55,7 +80,49 @@ pub fn deny_warnings() -> bool {
 
 #[inline(always)]
 pub fn cache_content() -> bool {
-    settings().cache_content
+    CACHE_CONTENT_OVERRIDE
+        .get()
+        .unwrap_or_else(|| settings().cache_content)
+}
+
+/// Restores the previous cache setting override when dropped.
+#[must_use]
+pub struct CacheContentOverride(Option<bool>);
+
+impl Drop for CacheContentOverride {
+    fn drop(&mut self) {
+        CACHE_CONTENT_OVERRIDE.set(self.0);
+    }
+}
+
+/// Make `cache_content()` return `cache` on the current thread until the returned
+/// guard is dropped. Work spawned onto other threads still sees the configured setting.
+pub fn override_cache_content(cache: bool) -> CacheContentOverride {
+    CacheContentOverride(CACHE_CONTENT_OVERRIDE.replace(Some(cache)))
+}
+
+/// Whether cached content should be read fresh and written back on the current thread,
+/// see [`refresh_cache_content`].
+#[inline(always)]
+pub fn refresh_cache() -> bool {
+    REFRESH_CACHE.get()
+}
+
+/// Restores the previous cache refresh mode when dropped.
+#[must_use]
+pub struct CacheRefresh(bool);
+
+impl Drop for CacheRefresh {
+    fn drop(&mut self) {
+        REFRESH_CACHE.set(self.0);
+    }
+}
+
+/// Make cached readers skip their cache lookups and replace the cached entries with
+/// what they read on the current thread until the returned guard is dropped.
+/// Only has an effect while `cache_content()` is true.
+pub fn refresh_cache_content() -> CacheRefresh {
+    CacheRefresh(REFRESH_CACHE.replace(true))
 }
 
 pub static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
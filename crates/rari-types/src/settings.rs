@@ -105,6 +105,10 @@ impl Settings {
             "CONTENT_TRANSLATED_ROOT",
             std::env::var("TESTING_CONTENT_TRANSLATED_ROOT").unwrap(),
         );
+        std::env::set_var(
+            "GENERIC_CONTENT_ROOT",
+            std::env::var("TESTING_GENERIC_CONTENT_ROOT").unwrap(),
+        );
         std::env::set_var(
             "CACHE_CONTENT",
             std::env::var("TESTING_CACHE_CONTENT").unwrap(),
@@ -0,0 +1,16 @@
+/// Turns an html fragment into plain text by dropping its tags and decoding its entities.
+///
+/// Whitespace is kept as is.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    html_escape::decode_html_entities(&text).into_owned()
+}
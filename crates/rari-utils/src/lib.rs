@@ -1,3 +1,4 @@
 pub mod concat;
 pub mod error;
+pub mod html;
 pub mod io;